@@ -0,0 +1,326 @@
+//! Achievements, defined in `assets/data/achievements.ron` and unlocked from what
+//! happens in a run. Progress is kept in `save/achievements.ron`, an unlock pops up
+//! a toast and the achievements screen lists how far along each one is.
+//!
+//! Replays that are played back and course runs don't count.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::PlaySound;
+use crate::bones::BoneKind;
+use crate::competition::AiDog;
+use crate::replay::{ReplayState, RunMode, TICKS_PER_SECOND};
+use crate::{
+    data, AppState, BonePickupEvent, Combo, GameMode, GameStep, HawkHitEvent, RunTicks, Score,
+    TotalPoints,
+};
+
+const ACHIEVEMENTS_FILE: &str = "achievements.ron";
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_FADE_SECONDS: f32 = 0.5;
+const TOAST_TOP: f32 = 90.0;
+const TOAST_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const TOAST_TEXT_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+// Start: --- Data
+// What has to be reached, all but TotalBones within a single run
+#[derive(Deserialize, Clone, Copy)]
+enum Goal {
+    BonesInRun(u32),
+    ScoreInRun(u32),
+    GoldenBonesInRun(u32),
+    // Bones in one combo
+    Combo(u32),
+    SecondsWithoutHit(u32),
+    TotalBones(u32),
+}
+
+impl Goal {
+    fn target(self) -> u32 {
+        match self {
+            Goal::BonesInRun(target)
+            | Goal::ScoreInRun(target)
+            | Goal::GoldenBonesInRun(target)
+            | Goal::Combo(target)
+            | Goal::SecondsWithoutHit(target)
+            | Goal::TotalBones(target) => target,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AchievementDef {
+    // Key in the save file, stays the same when the name is reworded
+    id: String,
+    name: String,
+    description: String,
+    goal: Goal,
+}
+
+#[derive(Deserialize, Default)]
+struct AchievementConfig {
+    achievements: Vec<AchievementDef>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+struct Progress {
+    // Best value reached so far, the running total for TotalBones
+    best: u32,
+    unlocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct SavedProgress {
+    achievements: BTreeMap<String, Progress>,
+}
+// End: --- Data
+
+// Start: --- Resources
+pub struct Achievements {
+    defs: Vec<AchievementDef>,
+    saved: SavedProgress,
+}
+
+impl Achievements {
+    // One line per achievement for the achievements screen
+    pub fn lines(&self) -> Vec<String> {
+        self.defs
+            .iter()
+            .map(|def| {
+                let progress = self.progress(def);
+                let target = def.goal.target();
+                if progress.unlocked {
+                    format!("{}: {}  (unlocked)", def.name, def.description)
+                } else {
+                    format!(
+                        "{}: {}  ({}/{})",
+                        def.name,
+                        def.description,
+                        progress.best.min(target),
+                        target
+                    )
+                }
+            })
+            .collect()
+    }
+
+    fn progress(&self, def: &AchievementDef) -> Progress {
+        self.saved
+            .achievements
+            .get(&def.id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+// What the current run reached so far that isn't kept anywhere else
+#[derive(Default)]
+struct RunProgress {
+    golden_bones: u32,
+    longest_combo: u32,
+    last_hit_tick: u32,
+}
+
+#[derive(Default)]
+struct Toasts {
+    queue: VecDeque<String>,
+}
+// End: --- Resources
+
+// Start: --- Components
+#[derive(Component)]
+struct Toast {
+    age: f32,
+}
+// End: --- Components
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        let config: AchievementConfig = data::load(ACHIEVEMENTS_FILE);
+        app.insert_resource(Achievements {
+            defs: config.achievements,
+            saved: data::load_saved(ACHIEVEMENTS_FILE),
+        })
+        .init_resource::<RunProgress>()
+        .init_resource::<Toasts>()
+        .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(reset_run_progress))
+        .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(save_achievements))
+        .add_system(track_achievements.after(GameStep::HawkCollision))
+        .add_system(show_toasts.after(track_achievements));
+    }
+}
+
+fn reset_run_progress(mut run: ResMut<RunProgress>) {
+    *run = RunProgress::default();
+}
+
+// Progress of a run is only written once it ends, unlocks straight away
+fn save_achievements(achievements: Res<Achievements>) {
+    data::store(ACHIEVEMENTS_FILE, &achievements.saved);
+}
+
+fn track_achievements(
+    state: Res<State<AppState>>,
+    mode: Res<GameMode>,
+    replay: Option<Res<ReplayState>>,
+    ai: Option<Res<AiDog>>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    combo: Res<Combo>,
+    run_ticks: Res<RunTicks>,
+    mut run: ResMut<RunProgress>,
+    mut achievements: ResMut<Achievements>,
+    mut toasts: ResMut<Toasts>,
+    mut sounds: EventWriter<PlaySound>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+) {
+    let playback = replay.map_or(false, |replay| matches!(replay.mode, RunMode::Playback));
+    // AI dogs of a competition don't earn anything for the player
+    let ai = ai.is_some();
+    if *state.current() != AppState::InGame || playback || ai || *mode != GameMode::Balloon {
+        return;
+    }
+    let mut new_bones = 0;
+    for pickup in bone_pickups.iter() {
+        match pickup.kind {
+            BoneKind::Decoy => {}
+            BoneKind::Golden => {
+                run.golden_bones += 1;
+                new_bones += 1;
+            }
+            _ => new_bones += 1,
+        }
+    }
+    if hawk_hits.iter().count() > 0 {
+        run.last_hit_tick = run_ticks.0;
+    }
+    run.longest_combo = run.longest_combo.max(combo.chain);
+    let seconds_without_hit =
+        (run_ticks.0.saturating_sub(run.last_hit_tick) as f64 / TICKS_PER_SECOND) as u32;
+
+    let achievements = &mut *achievements;
+    let mut unlocked_any = false;
+    for def in achievements.defs.iter() {
+        let progress = achievements
+            .saved
+            .achievements
+            .entry(def.id.clone())
+            .or_default();
+        let value = match def.goal {
+            Goal::BonesInRun(_) => total_points.0,
+            Goal::ScoreInRun(_) => score.0,
+            Goal::GoldenBonesInRun(_) => run.golden_bones,
+            Goal::Combo(_) => run.longest_combo,
+            Goal::SecondsWithoutHit(_) => seconds_without_hit,
+            Goal::TotalBones(_) => progress.best + new_bones,
+        };
+        progress.best = progress.best.max(value);
+        if !progress.unlocked && progress.best >= def.goal.target() {
+            progress.unlocked = true;
+            unlocked_any = true;
+            toasts.queue.push_back(def.name.clone());
+        }
+    }
+    if unlocked_any {
+        sounds.send(PlaySound("achievement"));
+        data::store(ACHIEVEMENTS_FILE, &achievements.saved);
+    }
+}
+
+// Shows one unlock at a time at the top of the screen, fading out at the end
+fn show_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut toasts: ResMut<Toasts>,
+    mut shown: Query<(Entity, &mut Toast, &Children)>,
+    mut boxes: Query<(&mut UiColor, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if let Ok((entity, mut toast, rows)) = shown.get_single_mut() {
+        toast.age += time.delta_seconds();
+        if toast.age >= TOAST_SECONDS {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        let alpha = ((TOAST_SECONDS - toast.age) / TOAST_FADE_SECONDS).min(1.0);
+        for row in rows.iter() {
+            let (mut color, children) = match boxes.get_mut(*row) {
+                Ok(found) => found,
+                Err(_) => continue,
+            };
+            color.0.set_a(TOAST_COLOR.a() * alpha);
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].style.color.set_a(alpha);
+                }
+            }
+        }
+        return;
+    }
+    let name = match toasts.queue.pop_front() {
+        Some(name) => name,
+        None => return,
+    };
+    let font: Handle<Font> = asset_server.load(FONT);
+    let text = |value: String, font_size: f32, color: Color| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+            default(),
+        ),
+        ..default()
+    };
+    // A full width row to centre the toast in
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(TOAST_TOP),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: UiColor(Color::NONE),
+            ..default()
+        })
+        .insert(Toast { age: 0.0 })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        // Top to bottom
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        padding: Rect::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    color: UiColor(TOAST_COLOR),
+                    ..default()
+                })
+                .with_children(|toast| {
+                    toast.spawn_bundle(text(
+                        "Achievement unlocked".to_string(),
+                        20.0,
+                        Color::WHITE,
+                    ));
+                    toast.spawn_bundle(text(name, 32.0, TOAST_TEXT_COLOR));
+                });
+        });
+}
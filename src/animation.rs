@@ -0,0 +1,233 @@
+//! Sprite sheet animation clips defined in `assets/data/animations.ron`.
+//!
+//! Entities carry an `Animation` component holding the clip they currently play.
+//! Other systems switch clips with `Animation::play`.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{data, GameStep, Hawk, HawkHitEvent, Player, PlayerInput};
+
+const DOG_FLOAT: &str = "dog_float";
+const DOG_PADDLE: &str = "dog_paddle";
+const DOG_HURT: &str = "dog_hurt";
+const HAWK_FLY: &str = "hawk_fly";
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct SheetDef {
+    texture: String,
+    tile_size: (f32, f32),
+    columns: usize,
+    rows: usize,
+}
+
+#[derive(Deserialize)]
+enum LoopMode {
+    Loop,
+    PingPong,
+    Once,
+}
+
+#[derive(Deserialize)]
+struct ClipDef {
+    sheet: String,
+    frames: Vec<usize>,
+    frame_time: f32,
+    mode: LoopMode,
+    // Clip to switch to when a `Once` clip ends
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct AnimationConfig {
+    sheets: HashMap<String, SheetDef>,
+    clips: HashMap<String, ClipDef>,
+}
+// End: --- Data
+
+// Start: --- Resources
+struct AnimationLibrary {
+    clips: HashMap<String, ClipDef>,
+    atlases: HashMap<String, Handle<TextureAtlas>>,
+}
+// End: --- Resources
+
+// Start: --- Components
+#[derive(Component)]
+pub struct Animation {
+    clip: String,
+    // Position in the clip's frame list
+    step: usize,
+    elapsed: f32,
+    reverse: bool,
+    finished: bool,
+}
+
+impl Animation {
+    pub fn new(clip: &str) -> Self {
+        Animation {
+            clip: clip.to_string(),
+            step: 0,
+            elapsed: 0.0,
+            reverse: false,
+            finished: false,
+        }
+    }
+
+    // Switches to another clip, a clip that is already playing keeps going
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Animation::new(clip);
+        }
+    }
+
+    pub fn is_playing(&self, clip: &str) -> bool {
+        self.clip == clip && !self.finished
+    }
+}
+// End: --- Components
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_animations)
+            .add_system(attach_animations)
+            .add_system(player_animation_state.after(GameStep::HawkCollision))
+            .add_system(animate.after(player_animation_state));
+    }
+}
+
+fn load_animations(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let config: AnimationConfig = data::load("animations.ron");
+    let atlases = config
+        .sheets
+        .iter()
+        .map(|(name, sheet)| {
+            let atlas = TextureAtlas::from_grid(
+                asset_server.load(sheet.texture.as_str()),
+                Vec2::new(sheet.tile_size.0, sheet.tile_size.1),
+                sheet.columns,
+                sheet.rows,
+            );
+            (name.clone(), texture_atlases.add(atlas))
+        })
+        .collect();
+    for (name, clip) in config.clips.iter() {
+        if !config.sheets.contains_key(&clip.sheet) || clip.frames.is_empty() {
+            warn!("Animation clip {} has no frames or an unknown sheet", name);
+        }
+    }
+    commands.insert_resource(AnimationLibrary {
+        clips: config.clips,
+        atlases,
+    });
+}
+
+// Swaps the plain sprite of new dogs and hawks for their animated sprite sheet
+fn attach_animations(
+    mut commands: Commands,
+    library: Res<AnimationLibrary>,
+    query: Query<(Entity, &Sprite, Option<&Player>), Or<(Added<Player>, Added<Hawk>)>>,
+) {
+    for (entity, sprite, player) in query.iter() {
+        let clip_name = if player.is_some() {
+            DOG_FLOAT
+        } else {
+            HAWK_FLY
+        };
+        let (clip, atlas) = match library
+            .clips
+            .get(clip_name)
+            .and_then(|clip| library.atlases.get(&clip.sheet).map(|atlas| (clip, atlas)))
+        {
+            Some(found) => found,
+            None => continue,
+        };
+        commands
+            .entity(entity)
+            .remove::<Sprite>()
+            .remove::<Handle<Image>>()
+            .insert(TextureAtlasSprite {
+                index: clip.frames.first().copied().unwrap_or_default(),
+                color: sprite.color,
+                custom_size: sprite.custom_size,
+                ..default()
+            })
+            .insert(atlas.clone())
+            .insert(Animation::new(clip_name));
+    }
+}
+
+fn player_animation_state(
+    player_input: Res<PlayerInput>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+    mut query: Query<&mut Animation, With<Player>>,
+) {
+    let hit = hawk_hits.iter().count() > 0;
+    for mut animation in query.iter_mut() {
+        if hit {
+            animation.play(DOG_HURT);
+        } else if animation.is_playing(DOG_HURT) {
+            continue;
+        } else if player_input.flap {
+            animation.play(DOG_PADDLE);
+        } else {
+            animation.play(DOG_FLOAT);
+        }
+    }
+}
+
+fn animate(
+    time: Res<Time>,
+    library: Res<AnimationLibrary>,
+    mut query: Query<(&mut Animation, &mut TextureAtlasSprite)>,
+) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        let clip = match library.clips.get(&animation.clip) {
+            Some(clip) if !clip.frames.is_empty() => clip,
+            _ => continue,
+        };
+        animation.elapsed += time.delta_seconds();
+        while clip.frame_time > 0.0 && animation.elapsed >= clip.frame_time && !animation.finished {
+            animation.elapsed -= clip.frame_time;
+            advance(&mut animation, clip);
+        }
+        if animation.finished {
+            if let Some(next) = &clip.next {
+                animation.play(next);
+            }
+        }
+        sprite.index = clip.frames[animation.step.min(clip.frames.len() - 1)];
+    }
+}
+
+fn advance(animation: &mut Animation, clip: &ClipDef) {
+    let last = clip.frames.len() - 1;
+    match clip.mode {
+        LoopMode::Loop => animation.step = (animation.step + 1) % (last + 1),
+        LoopMode::Once if animation.step == last => animation.finished = true,
+        LoopMode::Once => animation.step += 1,
+        LoopMode::PingPong if last == 0 => {}
+        LoopMode::PingPong => {
+            if animation.step == last {
+                animation.reverse = true;
+            } else if animation.step == 0 {
+                animation.reverse = false;
+            }
+            if animation.reverse {
+                animation.step -= 1;
+            } else {
+                animation.step += 1;
+            }
+        }
+    }
+}
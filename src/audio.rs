@@ -0,0 +1,316 @@
+//! Sound effects and background music, defined in `assets/data/audio.ron`.
+//!
+//! Bevy plays a sound once from start to end and can't stop it or change its
+//! volume, so a music track is made of layers (stems) of the same length that are
+//! started together over and over. Every time round the track of the current state
+//! is picked, and during a run more layers join in the more hawks are on screen,
+//! outside of it only the calmest layer plays. A new track or layer waits for the
+//! end of the loop that is playing.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::bones::BoneKind;
+use crate::course::{ObstacleEvent, Outcome};
+use crate::judge::Call;
+use crate::settings::Settings;
+use crate::{data, AppState, BonePickupEvent, Hawk, HawkHitEvent, WINDOW_WIDTH};
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct LayerDef {
+    file: String,
+    // Hawks on screen from which the layer joins in
+    #[serde(default)]
+    intensity: usize,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AudioConfig {
+    // Sound effect name to file
+    sounds: HashMap<String, String>,
+    // Track name to its layers, all of the same length
+    tracks: HashMap<String, Vec<LayerDef>>,
+    // Track of each state, states without one keep whatever is playing
+    music: HashMap<AppState, String>,
+}
+// End: --- Data
+
+// Start: --- Events
+// Plays the named sound effect from audio.ron
+pub struct PlaySound(pub &'static str);
+// End: --- Events
+
+// Start: --- Resources
+struct SoundLibrary {
+    sounds: HashMap<String, Handle<AudioSource>>,
+    tracks: HashMap<String, Vec<MusicLayer>>,
+    music: HashMap<AppState, String>,
+}
+
+struct MusicLayer {
+    intensity: usize,
+    source: Handle<AudioSource>,
+}
+
+#[derive(Default)]
+struct MusicPlayer {
+    // Of the current state or the last state that had one
+    track: Option<String>,
+    // Seconds since startup when the layers playing now end
+    loop_end: f64,
+}
+// End: --- Resources
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySound>()
+            .init_resource::<MusicPlayer>()
+            .add_startup_system(load_audio)
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(play_game_over))
+            .add_system(play_sounds.after(play_game_over))
+            .add_system(play_music);
+    }
+}
+
+fn load_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let config: AudioConfig = data::load("audio.ron");
+    let sounds = config
+        .sounds
+        .iter()
+        .map(|(name, file)| (name.clone(), asset_server.load(file.as_str())))
+        .collect();
+    let tracks = config
+        .tracks
+        .into_iter()
+        .map(|(name, layers)| {
+            let layers = layers
+                .into_iter()
+                .map(|layer| MusicLayer {
+                    intensity: layer.intensity,
+                    source: asset_server.load(layer.file.as_str()),
+                })
+                .collect();
+            (name, layers)
+        })
+        .collect();
+    commands.insert_resource(SoundLibrary {
+        sounds,
+        tracks,
+        music: config.music,
+    });
+}
+
+fn play_game_over(mut sounds: EventWriter<PlaySound>) {
+    sounds.send(PlaySound("game_over"));
+}
+
+fn play_sounds(
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    library: Option<Res<SoundLibrary>>,
+    mut sounds: EventReader<PlaySound>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+    mut obstacle_events: EventReader<ObstacleEvent>,
+) {
+    let library = match library {
+        Some(library) => library,
+        None => return,
+    };
+    let names = sounds
+        .iter()
+        .map(|sound| sound.0)
+        .chain(bone_pickups.iter().map(|pickup| match pickup.kind {
+            BoneKind::Plain | BoneKind::Spinning => "bone_pickup",
+            BoneKind::Golden => "golden_pickup",
+            BoneKind::Decoy => "decoy_pickup",
+        }))
+        .chain(hawk_hits.iter().flat_map(|_| ["hawk_hit", "balloon_pop"]))
+        .chain(
+            obstacle_events
+                .iter()
+                .filter_map(|event| match (event.call, event.outcome) {
+                    (Call::Ignored, _) => None,
+                    (Call::Faults(0), _) => Some("obstacle_clear"),
+                    (_, Outcome::Knocked) => Some("bar_down"),
+                    _ => Some("obstacle_fault"),
+                }),
+        );
+    for name in names {
+        if !settings.sound_effects {
+            continue;
+        }
+        match library.sounds.get(name) {
+            Some(sound) => audio.play(sound.clone()),
+            None => warn!("No sound named {} in audio.ron", name),
+        }
+    }
+}
+
+// Starts the layers of the track again at the end of each loop. They only start
+// once all of them are loaded, so they are in sync and the length is known.
+fn play_music(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    settings: Res<Settings>,
+    audio: Res<Audio>,
+    sources: Res<Assets<AudioSource>>,
+    library: Option<Res<SoundLibrary>>,
+    mut player: ResMut<MusicPlayer>,
+    hawks: Query<&Transform, With<Hawk>>,
+) {
+    let library = match library {
+        Some(library) => library,
+        None => return,
+    };
+    if let Some(name) = library.music.get(state.current()) {
+        player.track = Some(name.clone());
+    }
+    let now = time.seconds_since_startup();
+    if now < player.loop_end {
+        return;
+    }
+    let name = match player.track.clone() {
+        Some(name) => name,
+        None => return,
+    };
+    let layers = match library.tracks.get(&name) {
+        Some(layers) => layers,
+        None => {
+            warn!("No music track named {} in audio.ron", name);
+            player.track = None;
+            return;
+        }
+    };
+    let loaded: Option<Vec<&AudioSource>> = layers
+        .iter()
+        .map(|layer| sources.get(&layer.source))
+        .collect();
+    let seconds = match loaded {
+        Some(loaded) => loaded
+            .iter()
+            .filter_map(|source| wav_seconds(&source.bytes))
+            .fold(0.0, f32::max),
+        None => return,
+    };
+    if seconds <= 0.0 {
+        warn!("Music track {} has no layer to time its loop by", name);
+        player.track = None;
+        return;
+    }
+    // Paused runs and menus drop to the calmest layer
+    let intensity = if *state.current() == AppState::InGame {
+        hawks
+            .iter()
+            .filter(|transform| transform.translation.x.abs() < WINDOW_WIDTH / 2.0)
+            .count()
+    } else {
+        0
+    };
+    // With the music off the loop goes round silently, so it can come back in time
+    if settings.music {
+        for layer in playing_layers(layers, intensity) {
+            audio.play(layer.source.clone());
+        }
+    }
+    player.loop_end = now + seconds as f64;
+}
+
+// Layers that play with `intensity` hawks on screen
+fn playing_layers(layers: &[MusicLayer], intensity: usize) -> impl Iterator<Item = &MusicLayer> {
+    layers
+        .iter()
+        .filter(move |layer| layer.intensity <= intensity)
+}
+
+// Length of a WAV file from its header, None if it isn't one
+fn wav_seconds(bytes: &[u8]) -> Option<f32> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let u32_at = |at: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+    };
+    let mut byte_rate = None;
+    let mut chunk = 12;
+    while let (Some(id), Some(size)) = (bytes.get(chunk..chunk + 4), u32_at(chunk + 4)) {
+        match id {
+            b"fmt " => byte_rate = u32_at(chunk + 16),
+            b"data" => return Some(size as f32 / byte_rate.filter(|rate| *rate > 0)? as f32),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        chunk += 8 + size as usize + size as usize % 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A header with the given chunks, `data_len` bytes of silence at 22050 Hz
+    fn wav(extra: &[u8], data_len: u32) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.extend(b"fmt ");
+        bytes.extend(16u32.to_le_bytes());
+        // PCM, mono, 22050 Hz, 44100 bytes a second, 16 bits
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(22050u32.to_le_bytes());
+        bytes.extend(44100u32.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(extra);
+        bytes.extend(b"data");
+        bytes.extend(data_len.to_le_bytes());
+        bytes.extend(vec![0; data_len as usize]);
+        bytes
+    }
+
+    #[test]
+    fn wav_length_from_the_header() {
+        assert_eq!(wav_seconds(&wav(&[], 44100)), Some(1.0));
+        assert_eq!(wav_seconds(&wav(&[], 22050)), Some(0.5));
+    }
+
+    #[test]
+    fn wav_length_skips_other_chunks() {
+        let mut list = b"LIST".to_vec();
+        list.extend(3u32.to_le_bytes());
+        // Odd length, padded
+        list.extend([1, 2, 3, 0]);
+        assert_eq!(wav_seconds(&wav(&list, 88200)), Some(2.0));
+    }
+
+    #[test]
+    fn wav_length_of_something_else() {
+        assert_eq!(wav_seconds(b"OggS and the rest"), None);
+        assert_eq!(wav_seconds(&wav(&[], 44100)[..30]), None);
+    }
+
+    #[test]
+    fn layers_join_in_with_more_hawks() {
+        let layers: Vec<MusicLayer> = [0, 1, 2, 3]
+            .into_iter()
+            .map(|intensity| MusicLayer {
+                intensity,
+                source: Handle::default(),
+            })
+            .collect();
+        let playing = |hawks| {
+            playing_layers(&layers, hawks)
+                .map(|layer| layer.intensity)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(playing(0), vec![0]);
+        assert_eq!(playing(2), vec![0, 1, 2]);
+        assert_eq!(playing(7), vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,79 @@
+//! Replays a recorded run without a window and checks the score it claims.
+//!
+//!     cargo run --bin verify_replay -- run.replay
+//!
+//! Prints the simulated score, bones, hawk hits and duration. Exits with 1 if they
+//! differ from the values stored in the replay and with 2 if the replay can't be read.
+
+use std::path::PathBuf;
+use std::process::exit;
+
+use agility_game::replay::{config_hash, Replay, ReplayState, TICKS_PER_SECOND};
+use agility_game::{AppState, GamePlugin, ReplayPlugin, RunArgs, Score, TotalPoints};
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("Usage: verify_replay <file>");
+            exit(2);
+        }
+    };
+    let replay = match Replay::load(&path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("Could not load replay {}", e);
+            exit(2);
+        }
+    };
+    if replay.config_hash != config_hash() {
+        eprintln!("Warning: replay was recorded with different game settings");
+    }
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .insert_resource(RunArgs {
+            replay: Some(path),
+            ..default()
+        })
+        .add_plugin(GamePlugin)
+        .add_plugin(ReplayPlugin);
+
+    // Step the simulation by hand as fast as possible, one update per tick. A run
+    // that diverged may also end early when the dog runs out of lives.
+    let ticks = replay.ticks();
+    loop {
+        app.update();
+        if app.world.resource::<ReplayState>().tick >= ticks
+            || *app.world.resource::<State<AppState>>().current() == AppState::GameOver
+        {
+            break;
+        }
+    }
+
+    let points = app.world.resource::<TotalPoints>().0;
+    let score = app.world.resource::<Score>().0;
+    let hawk_hits = app.world.resource::<ReplayState>().hawk_hits;
+    // How far the simulation got, short of the replay if the dog ran out of lives
+    let simulated = app.world.resource::<ReplayState>().tick;
+    println!("Score: {} (claimed {})", score, replay.score);
+    println!("Bones: {} (claimed {})", points, replay.points);
+    println!("Hawk hits: {} (claimed {})", hawk_hits, replay.hawk_hits);
+    println!(
+        "Duration: {} ticks, {:.2} s (claimed {})",
+        simulated,
+        simulated as f64 / TICKS_PER_SECOND,
+        ticks
+    );
+
+    if score != replay.score || points != replay.points || hawk_hits != replay.hawk_hits {
+        println!("MISMATCH");
+        exit(1);
+    }
+    println!("OK");
+}
@@ -0,0 +1,118 @@
+//! Kinds of bones and how often each turns up, defined in `assets/data/bones.ron`.
+//!
+//! Bones are part of the simulation, so the file is included in the replay config
+//! hash: a replay recorded with other weights or values won't verify.
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::data;
+
+pub const BONES_FILE: &str = "bones.ron";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoneKind {
+    Plain,
+    // Worth more than a plain bone
+    Golden,
+    // Rare, moves in a sine wave and spins faster
+    Spinning,
+    // Looks like a bone but breaks the combo and scores nothing
+    Decoy,
+}
+
+// Start: --- Data
+#[derive(Deserialize, Debug)]
+pub struct BoneDef {
+    pub kind: BoneKind,
+    // Relative to the other weights
+    pub weight: u32,
+    // Points before the combo multiplier
+    pub points: u32,
+    pub texture: String,
+    // Turns per second
+    #[serde(default = "default_spin")]
+    pub spin: f32,
+    // Height of the sine wave in pixels, bones without one move straight
+    #[serde(default)]
+    pub wave_height: f32,
+    // Pixels travelled per wave
+    #[serde(default = "default_wave_length")]
+    pub wave_length: f32,
+}
+
+fn default_spin() -> f32 {
+    1.0
+}
+
+fn default_wave_length() -> f32 {
+    400.0
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BoneConfig {
+    pub kinds: Vec<BoneDef>,
+}
+
+impl Default for BoneConfig {
+    fn default() -> Self {
+        BoneConfig {
+            kinds: vec![BoneDef {
+                kind: BoneKind::Plain,
+                weight: 1,
+                points: 1,
+                texture: "sprites/bone-yellow.png".to_string(),
+                spin: default_spin(),
+                wave_height: 0.0,
+                wave_length: default_wave_length(),
+            }],
+        }
+    }
+}
+
+// Falls back to plain bones only when the file has no kind with a weight
+pub fn load_config() -> BoneConfig {
+    let config: BoneConfig = data::load(BONES_FILE);
+    if config.kinds.iter().all(|def| def.weight == 0) {
+        warn!("No bone kind with a weight in {}", BONES_FILE);
+        return BoneConfig::default();
+    }
+    config
+}
+// End: --- Data
+
+// Start: --- Resources
+pub struct BoneLibrary {
+    kinds: Vec<BoneDef>,
+    textures: Vec<Handle<Image>>,
+}
+
+impl BoneLibrary {
+    pub fn load(asset_server: &AssetServer) -> Self {
+        let kinds = load_config().kinds;
+        let textures = kinds
+            .iter()
+            .map(|def| asset_server.load(def.texture.as_str()))
+            .collect();
+        BoneLibrary { kinds, textures }
+    }
+
+    // Picks the kind of the next bone, a draw from the simulation's rng
+    pub fn roll(&self, rng: &mut StdRng) -> usize {
+        let indices: Vec<usize> = (0..self.kinds.len()).collect();
+        *indices
+            .choose_weighted(rng, |index| self.kinds[*index].weight)
+            .unwrap()
+    }
+
+    pub fn def(&self, index: usize) -> &BoneDef {
+        &self.kinds[index]
+    }
+
+    pub fn texture(&self, index: usize) -> Handle<Image> {
+        self.textures[index].clone()
+    }
+}
+// End: --- Resources
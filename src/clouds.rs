@@ -0,0 +1,154 @@
+//! Procedurally generated cloud field defined in `assets/data/clouds.ron`.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::{data, ScenerySeed, SimulationRunning, WINDOW_WIDTH};
+
+const CLOUD_RNG_STREAM: u64 = 2;
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct CloudConfig {
+    texture: String,
+    base_size: (f32, f32),
+    target_count: usize,
+    min_gap: f32,
+    // (far, near) ranges, picked by the depth of a cloud
+    scale: (f32, f32),
+    speed: (f32, f32),
+    z: (f32, f32),
+    // Ranges picked independently
+    alpha: (f32, f32),
+    y: (f32, f32),
+}
+
+impl Default for CloudConfig {
+    fn default() -> Self {
+        CloudConfig {
+            texture: "sprites/cloud.png".to_string(),
+            base_size: (400.0, 200.0),
+            target_count: 3,
+            min_gap: 200.0,
+            scale: (0.5, 1.0),
+            speed: (0.2, 0.6),
+            z: (-9.0, -1.0),
+            alpha: (0.7, 1.0),
+            y: (100.0, 350.0),
+        }
+    }
+}
+// End: --- Data
+
+// Start: --- Components
+#[derive(Component)]
+struct Cloud {
+    speed: f32,
+    half_width: f32,
+}
+// End: --- Components
+
+// Start: --- Resources
+struct CloudField {
+    config: CloudConfig,
+    texture: Handle<Image>,
+    rng: StdRng,
+}
+// End: --- Resources
+
+pub struct CloudPlugin;
+
+impl Plugin for CloudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_cloud_field)
+            .add_system(cloud_mover.with_run_criteria(SimulationRunning))
+            .add_system(
+                cloud_spawner
+                    .after(cloud_mover)
+                    .with_run_criteria(SimulationRunning),
+            );
+    }
+}
+
+fn lerp((from, to): (f32, f32), t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn spawn_cloud(commands: &mut Commands, field: &mut CloudField, x: Option<f32>) {
+    let config = &field.config;
+    let rng = &mut field.rng;
+    let depth: f32 = rng.gen();
+    let scale = lerp(config.scale, depth);
+    let size = Vec2::new(config.base_size.0, config.base_size.1) * scale;
+    // Without a position the cloud enters from just beyond the right edge
+    let x = x.unwrap_or((WINDOW_WIDTH + size.x) / 2.0);
+    let y = lerp(config.y, rng.gen());
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(size),
+                color: Color::rgba(1.0, 1.0, 1.0, lerp(config.alpha, rng.gen())),
+                ..default()
+            },
+            texture: field.texture.clone(),
+            transform: Transform::from_xyz(x, y, lerp(config.z, depth)),
+            ..default()
+        })
+        .insert(Cloud {
+            speed: lerp(config.speed, depth),
+            half_width: size.x / 2.0,
+        });
+}
+
+fn setup_cloud_field(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scenery_seed: Res<ScenerySeed>,
+) {
+    let config: CloudConfig = data::load("clouds.ron");
+    let mut field = CloudField {
+        texture: asset_server.load(config.texture.as_str()),
+        config,
+        rng: scenery_seed.rng(CLOUD_RNG_STREAM),
+    };
+    // Start with the sky already filled
+    for _ in 0..field.config.target_count {
+        let x = field.rng.gen_range(-WINDOW_WIDTH / 2.0..WINDOW_WIDTH / 2.0);
+        spawn_cloud(&mut commands, &mut field, Some(x));
+    }
+    commands.insert_resource(field);
+}
+
+fn cloud_mover(mut commands: Commands, mut clouds: Query<(Entity, &mut Transform, &Cloud)>) {
+    for (entity, mut transform, cloud) in clouds.iter_mut() {
+        transform.translation.x -= cloud.speed;
+        if transform.translation.x < -(WINDOW_WIDTH / 2.0 + cloud.half_width) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn cloud_spawner(
+    mut commands: Commands,
+    field: Option<ResMut<CloudField>>,
+    clouds: Query<(&Transform, &Cloud)>,
+) {
+    let mut field = match field {
+        Some(field) => field,
+        None => return,
+    };
+    let on_screen = clouds
+        .iter()
+        .filter(|(transform, cloud)| {
+            transform.translation.x > -(WINDOW_WIDTH / 2.0 + cloud.half_width)
+        })
+        .count();
+    // Wait until the newest cloud has moved far enough in to keep them spread out
+    let entry_clear = clouds.iter().all(|(transform, cloud)| {
+        transform.translation.x + cloud.half_width + field.config.min_gap < WINDOW_WIDTH / 2.0
+    });
+    if on_screen < field.config.target_count && entry_clear {
+        spawn_cloud(&mut commands, &mut field, None);
+    }
+}
@@ -0,0 +1,612 @@
+//! Competition mode: players taking turns at the keyboard and AI dogs run one after
+//! the other, all on the same course or, in the balloon, on the same seed for the
+//! same length of time. The results go into a ranking table, on a course by faults
+//! and then time with eliminated runs last, in the balloon by score. The final
+//! standings can be exported as CSV and JSON to `save`.
+//!
+//! AI dogs play through PlayerInput like a player would, so their balloon runs are
+//! recorded and replayed like any other. They don't count for achievements or
+//! statistics.
+
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::bones::BoneKind;
+use crate::course::{format_course_time, CourseRun, SelectedCourse};
+use crate::dog::DogClass;
+use crate::replay::TICKS_PER_SECOND;
+use crate::{
+    data, AppState, Bone, GameMode, GameStep, Hawk, Player, PlayerInput, RunTicks, Score,
+    SimulationRunning, TotalPoints,
+};
+
+const MAX_PLAYERS: u32 = 4;
+// Balloon runs of a competition end after this long, if the dog is still flying
+const BALLOON_RUN_SECONDS: f64 = 120.0;
+// Names and skill from 0.0 to 1.0 of the AI dogs, in the order they join
+const AI_DOGS: [(&str, f32); 4] = [("Rex", 0.9), ("Bella", 0.7), ("Max", 0.5), ("Luna", 0.3)];
+// Ticks between an AI dog's decisions in the balloon at skill 0.0, every tick at 1.0
+const AI_THINK_TICKS: f32 = 20.0;
+// Pixels an AI dog at skill 0.0 may misjudge its height in the balloon by
+const AI_JITTER: f32 = 80.0;
+// Hawks closer than this ahead and this close in height are flown away from
+const AI_HAWK_RANGE: f32 = 300.0;
+const AI_HAWK_HEIGHT: f32 = 100.0;
+// How far above or below a hawk an AI dog tries to pass
+const AI_HAWK_CLEARANCE: f32 = 160.0;
+const AI_BONE_RANGE: f32 = 500.0;
+
+// Start: --- Setup
+// Chosen on the competition menu
+pub struct CompetitionSetup {
+    pub mode: GameMode,
+    pub players: u32,
+    pub ai_dogs: u32,
+}
+
+impl Default for CompetitionSetup {
+    fn default() -> Self {
+        CompetitionSetup {
+            mode: GameMode::Course,
+            players: 2,
+            ai_dogs: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SetupOption {
+    Mode,
+    Players,
+    AiDogs,
+}
+
+impl SetupOption {
+    pub const ALL: [SetupOption; 3] =
+        [SetupOption::Mode, SetupOption::Players, SetupOption::AiDogs];
+
+    pub fn label(self, setup: &CompetitionSetup) -> String {
+        match self {
+            SetupOption::Mode => format!("Event: {}", mode_name(setup.mode)),
+            SetupOption::Players => format!("Players: {}", setup.players),
+            SetupOption::AiDogs => format!("AI dogs: {}", setup.ai_dogs),
+        }
+    }
+
+    // Up or down by one, round at the ends
+    pub fn change(self, setup: &mut CompetitionSetup, up: bool) {
+        let step = |value: u32, min: u32, max: u32| match up {
+            true if value >= max => min,
+            true => value + 1,
+            false if value <= min => max,
+            false => value - 1,
+        };
+        match self {
+            SetupOption::Mode => {
+                setup.mode = match setup.mode {
+                    GameMode::Balloon => GameMode::Course,
+                    GameMode::Course => GameMode::Balloon,
+                }
+            }
+            SetupOption::Players => setup.players = step(setup.players, 1, MAX_PLAYERS),
+            SetupOption::AiDogs => setup.ai_dogs = step(setup.ai_dogs, 0, AI_DOGS.len() as u32),
+        }
+    }
+}
+// End: --- Setup
+
+// Start: --- Resources
+struct Competitor {
+    name: String,
+    // AI dogs only
+    skill: Option<f32>,
+}
+
+#[derive(Clone)]
+enum RunResult {
+    Balloon {
+        score: u32,
+        bones: u32,
+        ticks: u32,
+    },
+    Course {
+        faults: f32,
+        ticks: u32,
+        eliminated: Option<String>,
+    },
+}
+
+impl RunResult {
+    // Better results first
+    fn rank(&self, other: &RunResult) -> Ordering {
+        match (self, other) {
+            (
+                RunResult::Balloon { score, bones, .. },
+                RunResult::Balloon {
+                    score: other_score,
+                    bones: other_bones,
+                    ..
+                },
+            ) => other_score.cmp(score).then(other_bones.cmp(bones)),
+            (
+                RunResult::Course {
+                    faults,
+                    ticks,
+                    eliminated,
+                },
+                RunResult::Course {
+                    faults: other_faults,
+                    ticks: other_ticks,
+                    eliminated: other_eliminated,
+                },
+            ) => eliminated
+                .is_some()
+                .cmp(&other_eliminated.is_some())
+                .then(faults.partial_cmp(other_faults).unwrap_or(Ordering::Equal))
+                .then(ticks.cmp(other_ticks)),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn eliminated(&self) -> bool {
+        matches!(
+            self,
+            RunResult::Course {
+                eliminated: Some(_),
+                ..
+            }
+        )
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            RunResult::Balloon {
+                score,
+                bones,
+                ticks,
+            } => format!(
+                "Score {}  Bones {}  {}",
+                score,
+                bones,
+                format_course_time(*ticks)
+            ),
+            RunResult::Course {
+                eliminated: Some(reason),
+                ..
+            } => format!("Eliminated: {}", reason),
+            RunResult::Course { faults, ticks, .. } => {
+                format!("{:.2} faults  {}", faults, format_course_time(*ticks))
+            }
+        }
+    }
+}
+
+// A row of the exported standings
+#[derive(Serialize)]
+struct Standing {
+    // None for eliminated runs
+    rank: Option<usize>,
+    competitor: String,
+    ai: bool,
+    score: Option<u32>,
+    bones: Option<u32>,
+    faults: Option<f32>,
+    seconds: f64,
+    eliminated: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Standings {
+    event: String,
+    standings: Vec<Standing>,
+}
+
+// The competition under way, without a mode when there is none
+#[derive(Default)]
+pub struct Competition {
+    mode: Option<GameMode>,
+    // What everyone runs: the seed of the balloon or the course file
+    seed: u64,
+    course: String,
+    // Everyone runs as the class chosen when the competition started
+    class: DogClass,
+    competitors: Vec<Competitor>,
+    // Of the competitors who ran, in order
+    results: Vec<RunResult>,
+    // What the last export did
+    message: String,
+}
+
+impl Competition {
+    pub fn new(setup: &CompetitionSetup, course: &SelectedCourse, class: DogClass) -> Self {
+        let players = (1..=setup.players).map(|number| Competitor {
+            name: format!("Player {}", number),
+            skill: None,
+        });
+        let ai_dogs = AI_DOGS
+            .iter()
+            .take(setup.ai_dogs as usize)
+            .map(|(name, skill)| Competitor {
+                name: name.to_string(),
+                skill: Some(*skill),
+            });
+        Competition {
+            mode: Some(setup.mode),
+            seed: thread_rng().gen(),
+            course: course.0.clone(),
+            class,
+            competitors: players.chain(ai_dogs).collect(),
+            results: vec![],
+            message: String::new(),
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.mode.is_some()
+    }
+
+    // Balloon runs of a competition all fly the same seed
+    pub fn seed(&self) -> Option<u64> {
+        (self.mode == Some(GameMode::Balloon)).then(|| self.seed)
+    }
+
+    pub fn class(&self) -> Option<DogClass> {
+        self.active().then(|| self.class)
+    }
+
+    // Whose turn it is
+    fn current(&self) -> Option<&Competitor> {
+        self.competitors.get(self.results.len())
+    }
+
+    pub fn finished(&self) -> bool {
+        self.active() && self.current().is_none()
+    }
+
+    fn event(&self) -> String {
+        let event = match self.mode {
+            Some(GameMode::Course) => format!("Course {}", self.course.trim_end_matches(".ron")),
+            _ => format!("Balloon seed {}", self.seed),
+        };
+        format!("{}, {} dogs", event, self.class.name().to_lowercase())
+    }
+
+    // Who just ran and who is next, for the game over screen
+    pub fn after_run(&self) -> Option<(&str, String)> {
+        let last = self.competitors.get(self.results.len().checked_sub(1)?)?;
+        let next = match self.current() {
+            Some(next) => format!("Next up: {}", next.name),
+            None => "That was the last run".to_string(),
+        };
+        Some((&last.name, next))
+    }
+
+    // Competitor indexes with their results, best first and ties in running order
+    fn ranking(&self) -> Vec<(usize, &RunResult)> {
+        let mut ranking: Vec<(usize, &RunResult)> = self.results.iter().enumerate().collect();
+        ranking.sort_by(|(_, a), (_, b)| a.rank(b));
+        ranking
+    }
+
+    // Shared ranks for equal results, none for eliminations
+    fn ranks(&self) -> Vec<(Option<usize>, usize, &RunResult)> {
+        let ranking = self.ranking();
+        ranking
+            .iter()
+            .map(|(index, result)| {
+                let rank = (!result.eliminated()).then(|| {
+                    1 + ranking
+                        .iter()
+                        .filter(|(_, other)| other.rank(result) == Ordering::Less)
+                        .count()
+                });
+                (rank, *index, *result)
+            })
+            .collect()
+    }
+
+    // Lines for the standings screen
+    pub fn table(&self) -> Vec<String> {
+        let mut lines = vec![self.event()];
+        for (rank, index, result) in self.ranks() {
+            let rank = rank.map_or("-".to_string(), |rank| format!("{}.", rank));
+            lines.push(format!(
+                "{} {}  {}",
+                rank,
+                self.competitors[index].name,
+                result.describe()
+            ));
+        }
+        if !self.message.is_empty() {
+            lines.push(self.message.clone());
+        }
+        lines
+    }
+
+    fn standings(&self) -> Standings {
+        let standings = self
+            .ranks()
+            .into_iter()
+            .map(|(rank, index, result)| {
+                let competitor = &self.competitors[index];
+                let mut standing = Standing {
+                    rank,
+                    competitor: competitor.name.clone(),
+                    ai: competitor.skill.is_some(),
+                    score: None,
+                    bones: None,
+                    faults: None,
+                    seconds: 0.0,
+                    eliminated: None,
+                };
+                match result {
+                    RunResult::Balloon {
+                        score,
+                        bones,
+                        ticks,
+                    } => {
+                        standing.score = Some(*score);
+                        standing.bones = Some(*bones);
+                        standing.seconds = seconds(*ticks);
+                    }
+                    RunResult::Course {
+                        faults,
+                        ticks,
+                        eliminated,
+                    } => {
+                        standing.faults = Some(*faults);
+                        standing.seconds = seconds(*ticks);
+                        standing.eliminated = eliminated.clone();
+                    }
+                }
+                standing
+            })
+            .collect();
+        Standings {
+            event: self.event(),
+            standings,
+        }
+    }
+
+    // Writes the standings to `save` as CSV and JSON
+    pub fn export(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let name = format!("standings-{}", now.as_secs());
+        let standings = self.standings();
+        let json = match serde_json::to_string_pretty(&standings) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Could not serialize the standings: {}", e);
+                String::new()
+            }
+        };
+        let written = !json.is_empty()
+            && data::store_text(&format!("{}.csv", name), &csv(&standings))
+            && data::store_text(&format!("{}.json", name), &json);
+        self.message = match written {
+            true => format!("Exported to {}/{}.csv and .json", data::SAVE_DIR, name),
+            false => "Could not export the standings".to_string(),
+        };
+    }
+}
+
+// The AI dog running now
+pub struct AiDog {
+    skill: f32,
+    rng: StdRng,
+    // Balloon: the height the dog is making for and ticks until it thinks again
+    target: f32,
+    think_ticks: u32,
+    // Course: where Space goes down, off by the dog's misjudgement
+    presses: Vec<f32>,
+}
+// End: --- Resources
+
+pub struct CompetitionPlugin;
+
+impl Plugin for CompetitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompetitionSetup>()
+            .init_resource::<Competition>()
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(start_turn))
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(record_result))
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(end_competition))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(SimulationRunning)
+                    .with_system(ai_input.after(GameStep::Input).before(GameStep::Movement))
+                    .with_system(end_balloon_run.after(GameStep::HawkCollision)),
+            );
+    }
+}
+
+fn mode_name(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Balloon => "Balloon",
+        GameMode::Course => "Agility course",
+    }
+}
+
+fn seconds(ticks: u32) -> f64 {
+    ticks as f64 / TICKS_PER_SECOND
+}
+
+fn csv(standings: &Standings) -> String {
+    let mut text = "rank,competitor,ai,score,bones,faults,seconds,eliminated\n".to_string();
+    let field = |value: Option<String>| value.unwrap_or_default();
+    for standing in standings.standings.iter() {
+        text.push_str(&format!(
+            "{},\"{}\",{},{},{},{},{:.2},\"{}\"\n",
+            field(standing.rank.map(|rank| rank.to_string())),
+            standing.competitor,
+            standing.ai,
+            field(standing.score.map(|score| score.to_string())),
+            field(standing.bones.map(|bones| bones.to_string())),
+            field(standing.faults.map(|faults| format!("{:.2}", faults))),
+            standing.seconds,
+            field(standing.eliminated.clone()).replace('"', "\"\""),
+        ));
+    }
+    text
+}
+
+// Hands the keyboard to the next player or the run to the next AI dog
+fn start_turn(mut commands: Commands, competition: Res<Competition>) {
+    commands.remove_resource::<AiDog>();
+    let competitor = match competition.current() {
+        Some(competitor) => competitor,
+        None => return,
+    };
+    info!("Competition run of {}", competitor.name);
+    if let Some(skill) = competitor.skill {
+        let index = competition.results.len() as u64;
+        commands.insert_resource(AiDog {
+            skill,
+            rng: StdRng::seed_from_u64(competition.seed ^ index),
+            target: 0.0,
+            think_ticks: 0,
+            presses: vec![],
+        });
+    }
+}
+
+fn record_result(
+    mut competition: ResMut<Competition>,
+    mode: Res<GameMode>,
+    course: Option<Res<CourseRun>>,
+    score: Res<Score>,
+    total_points: Res<TotalPoints>,
+    run_ticks: Res<RunTicks>,
+) {
+    if competition.current().is_none() || competition.mode != Some(*mode) {
+        return;
+    }
+    let result = match (*mode, course) {
+        (GameMode::Course, Some(course)) => {
+            let eliminated = match course.judge.eliminated() {
+                Some(reason) => Some(reason.to_string()),
+                None if !course.finished() => Some("Did not finish".to_string()),
+                None => None,
+            };
+            RunResult::Course {
+                faults: course.judge.total_faults(course.time()),
+                ticks: course.time(),
+                eliminated,
+            }
+        }
+        (GameMode::Course, None) => return,
+        (GameMode::Balloon, _) => RunResult::Balloon {
+            score: score.0,
+            bones: total_points.0,
+            ticks: run_ticks.0,
+        },
+    };
+    competition.results.push(result);
+}
+
+// Leaving for the main menu ends a competition, finished or not
+fn end_competition(mut competition: ResMut<Competition>, mut commands: Commands) {
+    *competition = Competition::default();
+    commands.remove_resource::<AiDog>();
+}
+
+// Everyone gets the same time in the balloon
+fn end_balloon_run(
+    competition: Res<Competition>,
+    run_ticks: Res<RunTicks>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if competition.seed().is_some()
+        && competition.current().is_some()
+        && seconds(run_ticks.0) >= BALLOON_RUN_SECONDS
+    {
+        let _ = state.set(AppState::GameOver);
+    }
+}
+
+fn ai_input(
+    ai: Option<ResMut<AiDog>>,
+    mode: Res<GameMode>,
+    class: Res<DogClass>,
+    course: Option<Res<CourseRun>>,
+    mut player_input: ResMut<PlayerInput>,
+    player_query: Query<&Transform, With<Player>>,
+    bone_query: Query<(&Bone, &Transform)>,
+    hawk_query: Query<&Transform, With<Hawk>>,
+) {
+    let mut ai = match ai {
+        Some(ai) => ai,
+        None => return,
+    };
+    let flap = match (*mode, course) {
+        (GameMode::Course, Some(course)) => course_input(&mut ai, &course),
+        (GameMode::Course, None) => false,
+        (GameMode::Balloon, _) => match player_query.iter().next() {
+            Some(player) => balloon_input(&mut ai, *class, player, &bone_query, &hawk_query),
+            None => false,
+        },
+    };
+    *player_input = PlayerInput { flap };
+}
+
+// Space on the ticks the dog reaches where it thinks it should press
+fn course_input(ai: &mut AiDog, course: &CourseRun) -> bool {
+    if ai.presses.is_empty() {
+        let spread = 2.0 * (1.0 - ai.skill);
+        let rng = &mut ai.rng;
+        ai.presses = course
+            .press_points()
+            .into_iter()
+            .map(|(x, tolerance)| x + rng.gen_range(-1.0..=1.0) * spread * tolerance)
+            .collect();
+    }
+    let distance = course.distance();
+    ai.presses
+        .iter()
+        .any(|x| distance < *x && distance + course.speed() >= *x)
+}
+
+// Makes for the nearest bone ahead and away from hawks about to arrive
+fn balloon_input(
+    ai: &mut AiDog,
+    class: DogClass,
+    player: &Transform,
+    bone_query: &Query<(&Bone, &Transform)>,
+    hawk_query: &Query<&Transform, With<Hawk>>,
+) -> bool {
+    let position = player.translation;
+    if ai.think_ticks > 0 {
+        ai.think_ticks -= 1;
+        return position.y < ai.target;
+    }
+    ai.think_ticks = ((1.0 - ai.skill) * AI_THINK_TICKS) as u32;
+    let ahead = |x: f32, range: f32| x > position.x - 20.0 && x < position.x + range;
+    let hawk = hawk_query
+        .iter()
+        .map(|transform| transform.translation)
+        .filter(|hawk| ahead(hawk.x, AI_HAWK_RANGE))
+        .filter(|hawk| (hawk.y - position.y).abs() < AI_HAWK_HEIGHT)
+        .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal));
+    let bone = bone_query
+        .iter()
+        .filter(|(bone, _)| bone.kind != BoneKind::Decoy)
+        .map(|(_, transform)| transform.translation)
+        .filter(|bone| ahead(bone.x, AI_BONE_RANGE))
+        .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal));
+    let target = match (hawk, bone) {
+        (Some(hawk), _) if hawk.y > position.y => hawk.y - AI_HAWK_CLEARANCE,
+        (Some(hawk), _) => hawk.y + AI_HAWK_CLEARANCE,
+        (None, Some(bone)) => bone.y,
+        (None, None) => 0.0,
+    };
+    let jitter = ai.rng.gen_range(-1.0..=1.0) * (1.0 - ai.skill) * AI_JITTER;
+    ai.target = (target + jitter).clamp(class.ground_y(), class.flight_top());
+    position.y < ai.target
+}
@@ -0,0 +1,1383 @@
+//! Agility course mode: instead of flying, the dog runs a side-scrolling course of
+//! numbered obstacles read from `assets/courses`. Space jumps hurdles and the tire,
+//! taps out the rhythm through the weave poles and does nothing in the tunnel. The
+//! A-frame, dog walk and seesaw are run over, their yellow contact zones at either
+//! end have to be touched on the way. The jump bars and the tire are set for the
+//! size class of the dog, see `dog`.
+//!
+//! How a run is judged is in `judge`.
+//!
+//! Course runs are not recorded, replays are of balloon runs only.
+
+use bevy::{
+    ecs::{schedule::ShouldRun, system::EntityCommands},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::PlaySound;
+use crate::dog::DogClass;
+use crate::judge::{Call, Judge};
+use crate::replay::TICKS_PER_SECOND;
+use crate::{data, AppState, GameMode, GameStep, PlayerInput, RunEntity, SimulationRunning};
+
+const DEFAULT_COURSE: &str = "novice.ron";
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+
+// The dog stays at this x on screen while the course scrolls past
+pub const DOG_X: f32 = -400.0;
+// Where the paws are when the dog runs on the grass
+pub const GROUND_Y: f32 = -340.0;
+// Of a medium dog, the other classes are scaled
+const DOG_SIZE: f32 = 120.0;
+// The paws are this far below the middle of the dog sprite
+const PAWS_OFFSET: f32 = 29.0;
+// From the paws to the top of the back, what has to fit through the tire
+const DOG_BODY: f32 = 40.0;
+// Distance to the start line when a run begins
+const RUN_UP: f32 = 200.0;
+// Distance after the finish line before the run is over
+const RUN_OUT: f32 = 200.0;
+// After a refusal the dog goes back this far before the obstacle to try again
+const RETRY_DISTANCE: f32 = 150.0;
+// Ticks between the judge's whistle for an elimination and the end of the run
+const ELIMINATION_TICKS: u32 = 90;
+const DOG_Z: f32 = 10.0;
+// In the tunnel or on the far side of a weave pole
+const DOG_BEHIND_Z: f32 = 4.0;
+const OBSTACLE_Z: f32 = 6.0;
+
+// Taken off the upward speed of a jump every tick, the speed it starts with
+// depends on the class
+const GRAVITY: f32 = 0.45;
+// Anything that drops away faster than this under the paws leaves the dog in the air
+const STEP_DOWN: f32 = 4.0;
+
+// Obstacle sizes, heights above the grass. Bar and tire heights depend on the class.
+const JUMP_LENGTH: f32 = 20.0;
+const TIRE_LENGTH: f32 = 30.0;
+// Of the opening, the tire itself is a bit wider
+const TIRE_RADIUS: f32 = 40.0;
+const TIRE_SIZE: f32 = 112.0;
+const TUNNEL_LENGTH: f32 = 240.0;
+const TUNNEL_HEIGHT: f32 = 70.0;
+const WEAVE_POLES: usize = 12;
+const WEAVE_SPACING: f32 = 50.0;
+const WEAVE_HEIGHT: f32 = 100.0;
+// Space has to come within this distance of a pole to weave round it
+const WEAVE_WINDOW: f32 = 12.0;
+const AFRAME_RAMP: f32 = 200.0;
+const AFRAME_HEIGHT: f32 = 160.0;
+const DOG_WALK_RAMP: f32 = 180.0;
+const DOG_WALK_PLANK: f32 = 280.0;
+const DOG_WALK_HEIGHT: f32 = 110.0;
+const SEESAW_LENGTH: f32 = 360.0;
+const SEESAW_PIVOT: f32 = 55.0;
+// Ticks the seesaw takes to tip from one end to the other
+const SEESAW_TIP_TICKS: f32 = 30.0;
+// Along the ground, at both ends of the A-frame, dog walk and seesaw
+const CONTACT_LENGTH: f32 = 70.0;
+
+const PLANK_THICKNESS: f32 = 8.0;
+// Number plates hang this high above the grass
+const NUMBER_HEIGHT: f32 = 250.0;
+const BAR_COLOR: Color = Color::rgb(0.85, 0.15, 0.15);
+const POST_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const TUNNEL_COLOR: Color = Color::rgb(0.15, 0.35, 0.85);
+const TUNNEL_RIM_COLOR: Color = Color::rgb(0.1, 0.22, 0.6);
+const SURFACE_COLOR: Color = Color::rgb(0.2, 0.45, 0.8);
+const CONTACT_COLOR: Color = Color::rgb(1.0, 0.85, 0.1);
+const SUPPORT_COLOR: Color = Color::rgb(0.5, 0.5, 0.55);
+const PLATE_COLOR: Color = Color::WHITE;
+const PLATE_TEXT_COLOR: Color = Color::BLACK;
+const START_COLOR: Color = Color::rgb(0.2, 0.8, 0.3);
+const FINISH_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+// Start: --- Data
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Jump,
+    Tire,
+    Tunnel,
+    Weave,
+    AFrame,
+    DogWalk,
+    Seesaw,
+}
+
+impl ObstacleKind {
+    pub const ALL: [ObstacleKind; 7] = [
+        ObstacleKind::Jump,
+        ObstacleKind::Tire,
+        ObstacleKind::Tunnel,
+        ObstacleKind::Weave,
+        ObstacleKind::AFrame,
+        ObstacleKind::DogWalk,
+        ObstacleKind::Seesaw,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ObstacleKind::Jump => "Jump",
+            ObstacleKind::Tire => "Tire",
+            ObstacleKind::Tunnel => "Tunnel",
+            ObstacleKind::Weave => "Weave poles",
+            ObstacleKind::AFrame => "A-frame",
+            ObstacleKind::DogWalk => "Dog walk",
+            ObstacleKind::Seesaw => "Seesaw",
+        }
+    }
+
+    // Along the course, from where the obstacle starts
+    pub fn length(self) -> f32 {
+        match self {
+            ObstacleKind::Jump => JUMP_LENGTH,
+            ObstacleKind::Tire => TIRE_LENGTH,
+            ObstacleKind::Tunnel => TUNNEL_LENGTH,
+            ObstacleKind::Weave => WEAVE_POLES as f32 * WEAVE_SPACING,
+            ObstacleKind::AFrame => 2.0 * AFRAME_RAMP,
+            ObstacleKind::DogWalk => 2.0 * DOG_WALK_RAMP + DOG_WALK_PLANK,
+            ObstacleKind::Seesaw => SEESAW_LENGTH,
+        }
+    }
+
+    // Height of the top at `offset` from the start, for obstacles the dog runs over.
+    // `tilt` is how far a seesaw has tipped.
+    fn surface(self, offset: f32, tilt: f32) -> Option<f32> {
+        let length = self.length();
+        if !(0.0..=length).contains(&offset) {
+            return None;
+        }
+        match self {
+            ObstacleKind::AFrame => {
+                Some(AFRAME_HEIGHT * (1.0 - (offset - AFRAME_RAMP).abs() / AFRAME_RAMP))
+            }
+            ObstacleKind::DogWalk => {
+                let from_end = offset.min(length - offset);
+                Some(DOG_WALK_HEIGHT * (from_end / DOG_WALK_RAMP).min(1.0))
+            }
+            ObstacleKind::Seesaw => {
+                let along = 2.0 * offset / length - 1.0;
+                Some(SEESAW_PIVOT * (1.0 - tilt * along))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ObstacleDef {
+    pub number: u32,
+    pub kind: ObstacleKind,
+    // Where it starts, the start line is at 0
+    pub x: f32,
+}
+
+impl ObstacleDef {
+    pub fn end(&self) -> f32 {
+        self.x + self.kind.length()
+    }
+
+    pub fn label(&self) -> String {
+        match self.number {
+            0 => format!("unnumbered {}", self.kind.name().to_lowercase()),
+            number => format!("{} {}", number, self.kind.name()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Course {
+    pub name: String,
+    pub obstacles: Vec<ObstacleDef>,
+    // Where the finish line is
+    pub finish: f32,
+    // Seconds, time faults for every second over it
+    pub standard_time: f32,
+}
+
+impl Default for Course {
+    fn default() -> Self {
+        Course {
+            name: "Empty".to_string(),
+            obstacles: vec![],
+            finish: 1000.0,
+            standard_time: 30.0,
+        }
+    }
+}
+
+impl Course {
+    pub fn load(name: &str) -> Self {
+        let mut course: Course = data::load_course(name);
+        course.sort();
+        for problem in course.problems() {
+            warn!("Course {}: {}", name, problem);
+        }
+        course
+    }
+
+    // The dog meets them from left to right, whatever their numbers
+    pub fn sort(&mut self) {
+        self.obstacles
+            .sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    pub fn last_number(&self) -> u32 {
+        self.obstacles
+            .iter()
+            .map(|def| def.number)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // What is wrong with the course, with the obstacles sorted
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.standard_time <= 0.0 {
+            problems.push("The standard course time has to be above 0".to_string());
+        }
+        let last = self.last_number();
+        if last == 0 {
+            problems.push("There are no numbered obstacles".to_string());
+        }
+        for number in 1..=last {
+            match self
+                .obstacles
+                .iter()
+                .filter(|def| def.number == number)
+                .count()
+            {
+                0 => problems.push(format!("There is no obstacle {}", number)),
+                1 => {}
+                _ => problems.push(format!("There is more than one obstacle {}", number)),
+            }
+        }
+        // The dog meets them from left to right, so that has to be the course order
+        let numbered: Vec<&ObstacleDef> = self
+            .obstacles
+            .iter()
+            .filter(|def| def.number != 0)
+            .collect();
+        if let Some(pair) = numbered
+            .windows(2)
+            .find(|pair| pair[1].number < pair[0].number)
+        {
+            problems.push(format!(
+                "The {} stands before the {}",
+                pair[0].label(),
+                pair[1].label()
+            ));
+        }
+        for pair in self.obstacles.windows(2) {
+            if pair[0].end() > pair[1].x {
+                problems.push(format!(
+                    "The {} overlaps the {}",
+                    pair[0].label(),
+                    pair[1].label()
+                ));
+            }
+        }
+        for def in self.obstacles.iter() {
+            if def.x < 0.0 {
+                problems.push(format!("The {} is before the start line", def.label()));
+            }
+            if def.end() > self.finish {
+                problems.push(format!("The {} is past the finish line", def.label()));
+            }
+        }
+        problems
+    }
+}
+
+// File under `assets/courses` that course runs are on
+pub struct SelectedCourse(pub String);
+
+impl Default for SelectedCourse {
+    fn default() -> Self {
+        SelectedCourse(DEFAULT_COURSE.to_string())
+    }
+}
+// End: --- Data
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Clean,
+    // Bar down or tire hit
+    Knocked,
+    MissedContact,
+    MissedPole,
+    // Not taken at all: ran under the jump, over the tunnel...
+    RunPast,
+}
+
+impl Outcome {
+    pub fn describe(self, kind: ObstacleKind) -> &'static str {
+        match (self, kind) {
+            (Outcome::Clean, _) => "Clean",
+            (Outcome::Knocked, ObstacleKind::Jump) => "Bar down",
+            (Outcome::Knocked, ObstacleKind::Tire) => "Tire knocked",
+            (Outcome::Knocked, _) => "Knocked",
+            (Outcome::MissedContact, _) => "Missed contact",
+            (Outcome::MissedPole, _) => "Missed pole",
+            (Outcome::RunPast, _) => "Run past",
+        }
+    }
+}
+
+// Start: --- Events
+// The dog is done with an obstacle
+pub struct ObstacleEvent {
+    pub kind: ObstacleKind,
+    pub outcome: Outcome,
+    pub call: Call,
+    // Above the dog, for the popup
+    pub position: Vec3,
+}
+// End: --- Events
+
+// Start: --- Resources
+#[derive(Default)]
+struct ObstacleState {
+    outcome: Option<Outcome>,
+    // Obstacles with contacts: whether the dog got on and touched either zone
+    on: bool,
+    up_contact: bool,
+    down_contact: bool,
+    // Seesaw only, from -1.0 with the near end down to 1.0 with the far end down
+    tilt: f32,
+    // Weave poles: the pole the dog weaves round next, whether Space came for it
+    // and whether one was missed
+    pole: usize,
+    pole_taken: bool,
+    missed_pole: bool,
+}
+
+// The course of the current course run and how far the dog got through it
+pub struct CourseRun {
+    pub course: Course,
+    pub judge: Judge,
+    class: DogClass,
+    obstacles: Vec<ObstacleState>,
+    // Along the course, the start line is at 0
+    distance: f32,
+    // Of the paws above the grass
+    height: f32,
+    velocity: f32,
+    airborne: bool,
+    // Space is only acted on when it goes down
+    space_held: bool,
+    // Tunnel or weave poles the dog is in the middle of
+    inside: Option<usize>,
+    // Between the start and the finish line or the elimination
+    ticks: u32,
+    // Since the elimination
+    eliminated_ticks: u32,
+}
+
+impl CourseRun {
+    fn new(course: Course, class: DogClass) -> Self {
+        let obstacles = course
+            .obstacles
+            .iter()
+            .map(|_| ObstacleState {
+                tilt: -1.0,
+                ..default()
+            })
+            .collect();
+        CourseRun {
+            judge: Judge::new(&course, class.standard_time(course.standard_time)),
+            course,
+            class,
+            obstacles,
+            distance: -RUN_UP,
+            height: 0.0,
+            velocity: 0.0,
+            airborne: false,
+            space_held: false,
+            inside: None,
+            ticks: 0,
+            eliminated_ticks: 0,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.distance >= self.course.finish
+    }
+
+    // Past the run out or eliminated and off the course
+    pub fn over(&self) -> bool {
+        self.distance >= self.course.finish + RUN_OUT || self.eliminated_ticks >= ELIMINATION_TICKS
+    }
+
+    // Ticks from the start line to the finish line or until now
+    pub fn time(&self) -> u32 {
+        self.ticks
+    }
+
+    // Along the course, the start line is at 0
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    // Along the course per tick
+    pub fn speed(&self) -> f32 {
+        self.class.run_speed()
+    }
+
+    // How far before or after the top of a jump the paws are still above `height`.
+    // The dog moves in steps of a tick, so the top is a bit lower than a smooth arc
+    // would have it and a tick is left as a margin.
+    fn clearance(&self, height: f32) -> f32 {
+        let jump_speed = self.class.jump_speed();
+        let top = jump_speed * jump_speed / (2.0 * GRAVITY) - jump_speed / 2.0;
+        let ticks = (2.0 * (top - height).max(0.0) / GRAVITY).sqrt();
+        (ticks - 1.0).max(0.0) * self.speed()
+    }
+
+    // Where along the course Space has to go down for a clean run, with how far off
+    // it may be either way
+    pub fn press_points(&self) -> Vec<(f32, f32)> {
+        // The top of a jump, which should be over the bar or in the tire
+        let lead = self.class.jump_speed() / GRAVITY * self.speed();
+        let bar = self.clearance(self.class.bar_height());
+        let tire = self.clearance(self.class.tire_height() - TIRE_RADIUS);
+        let mut points = vec![];
+        for def in self.course.obstacles.iter().filter(|def| def.number != 0) {
+            let middle = def.x + def.kind.length() / 2.0;
+            match def.kind {
+                ObstacleKind::Jump => points.push((middle - lead, bar)),
+                ObstacleKind::Tire => points.push((middle - lead, tire)),
+                ObstacleKind::Weave => {
+                    points.extend((0..WEAVE_POLES).map(|pole| (def.x + pole_x(pole), WEAVE_WINDOW)))
+                }
+                _ => {}
+            }
+        }
+        points
+    }
+
+    // The obstacle the judge wants next
+    pub fn next_obstacle(&self) -> Option<&ObstacleDef> {
+        let next = self.judge.next();
+        self.course.obstacles.iter().find(|def| def.number == next)
+    }
+
+    // The judge's sheet for the game over screen
+    pub fn sheet(&self) -> Vec<String> {
+        self.judge.sheet(self.ticks, self.finished())
+    }
+
+    // Highest thing under the paws
+    fn floor(&self, distance: f32) -> f32 {
+        self.course
+            .obstacles
+            .iter()
+            .zip(self.obstacles.iter())
+            .filter_map(|(def, state)| def.kind.surface(distance - def.x, state.tilt))
+            .fold(0.0, f32::max)
+    }
+
+    // Moves the dog on by a tick. Returns whether it jumped and the obstacles it is
+    // done with, how it did on them and what the judge made of it.
+    fn step(&mut self, space: bool) -> (bool, Vec<(usize, Outcome, Call)>) {
+        let pressed = space && !self.space_held;
+        self.space_held = space;
+        let speed = self.speed();
+        self.distance += speed;
+        if self.judge.eliminated().is_some() {
+            self.eliminated_ticks += 1;
+        } else if self.distance > 0.0 && self.distance - speed < self.course.finish {
+            self.ticks += 1;
+            self.judge.check_time(self.ticks);
+            if self.finished() {
+                self.judge.finish();
+            }
+        }
+
+        let mut jumped = false;
+        match self.inside {
+            Some(index) if self.course.obstacles[index].kind == ObstacleKind::Weave => {
+                if pressed {
+                    self.weave(index);
+                }
+            }
+            // Nothing to jump in the tunnel
+            Some(_) => {}
+            None if pressed && !self.airborne => {
+                self.velocity = self.class.jump_speed();
+                self.airborne = true;
+                jumped = true;
+            }
+            None => {}
+        }
+
+        let floor = self.floor(self.distance);
+        if self.airborne {
+            self.velocity -= GRAVITY;
+            self.height += self.velocity;
+            if self.height <= floor {
+                self.height = floor;
+                self.velocity = 0.0;
+                self.airborne = false;
+            }
+        } else if floor < self.height - STEP_DOWN {
+            // Off the end of something that didn't come down, like a seesaw that
+            // hadn't tipped yet
+            self.velocity = 0.0;
+            self.airborne = true;
+        } else {
+            self.height = floor;
+        }
+
+        let mut done = vec![];
+        for index in 0..self.obstacles.len() {
+            if self.obstacles[index].outcome.is_some() {
+                continue;
+            }
+            let outcome = match self.outcome(index) {
+                Some(outcome) => outcome,
+                None => continue,
+            };
+            self.obstacles[index].outcome = Some(outcome);
+            let def = self.course.obstacles[index];
+            let call = self.judge.call(&def, outcome);
+            if call == Call::Refusal {
+                self.retry(index);
+            }
+            done.push((index, outcome, call));
+        }
+        (jumped, done)
+    }
+
+    // Back before an obstacle the dog refused, without ending up on the one before
+    fn retry(&mut self, index: usize) {
+        let def = self.course.obstacles[index];
+        let behind = self.course.obstacles[..index]
+            .iter()
+            .map(|def| def.x + def.kind.length())
+            .fold(0.0, f32::max);
+        self.distance = (def.x - RETRY_DISTANCE).max(behind);
+        self.obstacles[index] = ObstacleState {
+            tilt: -1.0,
+            ..default()
+        };
+        self.inside = None;
+        self.height = self.floor(self.distance);
+        self.velocity = 0.0;
+        self.airborne = false;
+    }
+
+    // Space in the weave poles, in time with the next pole or out of rhythm
+    fn weave(&mut self, index: usize) {
+        let offset = self.distance - self.course.obstacles[index].x;
+        let state = &mut self.obstacles[index];
+        if (offset - pole_x(state.pole)).abs() <= WEAVE_WINDOW && !state.pole_taken {
+            state.pole_taken = true;
+        } else {
+            state.missed_pole = true;
+        }
+    }
+
+    // How the dog did on an obstacle, once it is done with it
+    fn outcome(&mut self, index: usize) -> Option<Outcome> {
+        let def = self.course.obstacles[index];
+        let offset = self.distance - def.x;
+        let speed = self.speed();
+        let crossed = |point: f32| offset >= point && offset - speed < point;
+        let height = self.height;
+        let class = self.class;
+        let grounded = !self.airborne;
+        let length = def.kind.length();
+        let state = &mut self.obstacles[index];
+        match def.kind {
+            ObstacleKind::Jump => crossed(length / 2.0).then(|| match height {
+                _ if grounded => Outcome::RunPast,
+                height if height >= class.bar_height() => Outcome::Clean,
+                _ => Outcome::Knocked,
+            }),
+            ObstacleKind::Tire => crossed(length / 2.0).then(|| {
+                let tire = class.tire_height();
+                let opening = tire - TIRE_RADIUS..=tire + TIRE_RADIUS - DOG_BODY * class.scale();
+                match height {
+                    _ if grounded => Outcome::RunPast,
+                    height if opening.contains(&height) => Outcome::Clean,
+                    _ => Outcome::Knocked,
+                }
+            }),
+            ObstacleKind::Tunnel => {
+                if crossed(0.0) {
+                    if !grounded {
+                        return Some(Outcome::RunPast);
+                    }
+                    self.inside = Some(index);
+                } else if crossed(length) && self.inside == Some(index) {
+                    self.inside = None;
+                    return Some(Outcome::Clean);
+                }
+                None
+            }
+            ObstacleKind::Weave => {
+                if self.inside != Some(index) {
+                    if crossed(pole_x(0) - WEAVE_WINDOW) {
+                        if !grounded {
+                            return Some(Outcome::RunPast);
+                        }
+                        self.inside = Some(index);
+                    }
+                    return None;
+                }
+                if !crossed(pole_x(state.pole) + WEAVE_WINDOW) {
+                    return None;
+                }
+                if !state.pole_taken {
+                    state.missed_pole = true;
+                }
+                state.pole += 1;
+                state.pole_taken = false;
+                if state.pole < WEAVE_POLES {
+                    return None;
+                }
+                self.inside = None;
+                Some(if state.missed_pole {
+                    Outcome::MissedPole
+                } else {
+                    Outcome::Clean
+                })
+            }
+            ObstacleKind::AFrame | ObstacleKind::DogWalk | ObstacleKind::Seesaw => {
+                if (0.0..=length).contains(&offset) && grounded {
+                    state.on = true;
+                    if offset <= CONTACT_LENGTH {
+                        state.up_contact = true;
+                    }
+                    // A seesaw only once it is down on the ground
+                    let down = def.kind != ObstacleKind::Seesaw || state.tilt >= 1.0;
+                    if offset >= length - CONTACT_LENGTH && down {
+                        state.down_contact = true;
+                    }
+                }
+                // Tips once the dog is past the pivot
+                if def.kind == ObstacleKind::Seesaw && state.on && offset > length / 2.0 {
+                    state.tilt = (state.tilt + 2.0 / SEESAW_TIP_TICKS).min(1.0);
+                }
+                crossed(length).then(|| match state {
+                    state if !state.on => Outcome::RunPast,
+                    state if state.up_contact && state.down_contact => Outcome::Clean,
+                    _ => Outcome::MissedContact,
+                })
+            }
+        }
+    }
+
+    // Slope of what the dog runs on, or how it flies
+    fn dog_angle(&self) -> f32 {
+        if self.airborne {
+            return (self.velocity / self.speed()).atan() * 0.3;
+        }
+        let rise = self.floor(self.distance + 1.0) - self.floor(self.distance - 1.0);
+        (rise / 2.0).atan()
+    }
+
+    // In the tunnel or weaving round the far side of a pole
+    fn dog_behind(&self) -> bool {
+        match self.inside {
+            Some(index) => {
+                self.course.obstacles[index].kind == ObstacleKind::Tunnel
+                    || self.obstacles[index].pole % 2 == 1
+            }
+            None => false,
+        }
+    }
+}
+// End: --- Resources
+
+// Start: --- Components
+#[derive(Component)]
+struct CourseDog;
+
+// Scrolls with the course, `x` along the course
+#[derive(Component)]
+pub struct CoursePiece {
+    x: f32,
+}
+
+impl CoursePiece {
+    // With `distance` along the course where the dog is on screen
+    pub fn scroll(&self, transform: &mut Transform, distance: f32) {
+        transform.translation.x = DOG_X + self.x - distance;
+    }
+}
+
+// Index of the obstacle in the course
+#[derive(Component)]
+struct SeesawBoard(usize);
+
+#[derive(Component)]
+struct JumpBar(usize);
+// End: --- Components
+
+// Simulation of course runs, on top of SimulationRunning
+#[derive(RunCriteriaLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct CourseRunning;
+
+pub struct CoursePlugin;
+
+impl Plugin for CoursePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedCourse>()
+            .add_event::<ObstacleEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame)
+                    .with_system(start_course.after(GameStep::NewRun)),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(
+                        RunCriteria::pipe(SimulationRunning, course_mode).label(CourseRunning),
+                    )
+                    .with_system(run_course.label(GameStep::Movement).after(GameStep::Input))
+                    .with_system(finish_course.after(run_course)),
+            )
+            .add_system(scroll_course.after(run_course))
+            .add_system(show_obstacles.after(run_course))
+            .add_system(place_dog.after(run_course));
+    }
+}
+
+fn course_mode(In(should_run): In<ShouldRun>, mode: Res<GameMode>) -> ShouldRun {
+    match should_run {
+        ShouldRun::YesAndCheckAgain if *mode != GameMode::Course => ShouldRun::NoAndCheckAgain,
+        ShouldRun::Yes if *mode != GameMode::Course => ShouldRun::No,
+        should_run => should_run,
+    }
+}
+
+// Seconds with hundredths, the way agility times are given
+pub fn format_course_time(ticks: u32) -> String {
+    format!("{:.2} s", ticks as f64 / TICKS_PER_SECOND)
+}
+
+fn pole_x(pole: usize) -> f32 {
+    WEAVE_SPACING / 2.0 + pole as f32 * WEAVE_SPACING
+}
+
+fn block(centre: Vec2, size: Vec2, color: Color) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_translation(centre.extend(0.0)),
+        ..default()
+    }
+}
+
+// A plank whose top runs from `from` to `to`
+fn plank(from: Vec2, to: Vec2, color: Color) -> SpriteBundle {
+    let along = to - from;
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(along.length(), PLANK_THICKNESS)),
+            ..default()
+        },
+        transform: Transform {
+            translation: ((from + to) / 2.0 - Vec2::new(0.0, PLANK_THICKNESS / 2.0)).extend(0.0),
+            rotation: Quat::from_rotation_z(along.y.atan2(along.x)),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn label(
+    value: String,
+    font: &Handle<Font>,
+    font_size: f32,
+    color: Color,
+    position: Vec2,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+            TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        ),
+        transform: Transform::from_translation(position.extend(0.1)),
+        ..default()
+    }
+}
+
+// Everything on the course scrolls as one piece whose origin is on the grass
+fn spawn_piece<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    x: f32,
+    distance: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut piece = commands.spawn_bundle((
+        Transform::from_xyz(DOG_X + x - distance, GROUND_Y, OBSTACLE_Z),
+        GlobalTransform::default(),
+    ));
+    piece.insert(CoursePiece { x });
+    piece
+}
+
+// The obstacles, start and finish line of a course set for `class` and scrolled to
+// `distance`, returns the pieces spawned
+pub fn spawn_course(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    course: &Course,
+    class: DogClass,
+    distance: f32,
+) -> Vec<Entity> {
+    let font: Handle<Font> = asset_server.load(FONT);
+    let mut pieces = vec![];
+
+    for (index, def) in course.obstacles.iter().enumerate() {
+        let length = def.kind.length();
+        let mut piece = spawn_piece(commands, def.x, distance);
+        pieces.push(piece.id());
+        piece.with_children(|parent| {
+            // Obstacles numbered 0 aren't part of the course and have no plate
+            if def.number != 0 {
+                parent
+                    .spawn_bundle(block(
+                        Vec2::new(length / 2.0, NUMBER_HEIGHT),
+                        Vec2::splat(34.0),
+                        PLATE_COLOR,
+                    ))
+                    .with_children(|plate| {
+                        plate.spawn_bundle(label(
+                            def.number.to_string(),
+                            &font,
+                            26.0,
+                            PLATE_TEXT_COLOR,
+                            Vec2::ZERO,
+                        ));
+                    });
+            }
+            spawn_obstacle(parent, asset_server, index, def.kind, class);
+        });
+    }
+    for (x, text, color) in [
+        (0.0, "Start", START_COLOR),
+        (course.finish, "Finish", FINISH_COLOR),
+    ] {
+        let mut piece = spawn_piece(commands, x, distance);
+        pieces.push(piece.id());
+        piece.with_children(|parent| {
+            parent.spawn_bundle(block(
+                Vec2::new(0.0, 90.0),
+                Vec2::new(8.0, 180.0),
+                POST_COLOR,
+            ));
+            parent.spawn_bundle(block(Vec2::new(20.0, 165.0), Vec2::new(40.0, 28.0), color));
+            parent.spawn_bundle(label(
+                text.to_string(),
+                &font,
+                24.0,
+                Color::WHITE,
+                Vec2::new(0.0, 200.0),
+            ));
+        });
+    }
+    pieces
+}
+
+fn start_course(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mode: Res<GameMode>,
+    selected: Res<SelectedCourse>,
+    class: Res<DogClass>,
+) {
+    if *mode != GameMode::Course {
+        commands.remove_resource::<CourseRun>();
+        return;
+    }
+    let course = Course::load(&selected.0);
+    info!(
+        "Running course {} with a {} dog",
+        course.name,
+        class.name().to_lowercase()
+    );
+    for entity in spawn_course(&mut commands, &asset_server, &course, *class, -RUN_UP) {
+        commands.entity(entity).insert(RunEntity);
+    }
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(DOG_SIZE * class.scale())),
+                ..default()
+            },
+            texture: asset_server.load("sprites/standing-right.png"),
+            transform: Transform::from_xyz(DOG_X, GROUND_Y + PAWS_OFFSET * class.scale(), DOG_Z),
+            ..default()
+        })
+        .insert(CourseDog)
+        .insert(RunEntity);
+    commands.insert_resource(CourseRun::new(course, *class));
+}
+
+// The parts of an obstacle, relative to where it starts on the grass
+fn spawn_obstacle(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    index: usize,
+    kind: ObstacleKind,
+    class: DogClass,
+) {
+    let length = kind.length();
+    let middle = length / 2.0;
+    match kind {
+        ObstacleKind::Jump => {
+            let bar_height = class.bar_height();
+            let wing = bar_height + 40.0;
+            parent.spawn_bundle(block(
+                Vec2::new(middle, wing / 2.0),
+                Vec2::new(10.0, wing),
+                POST_COLOR,
+            ));
+            parent
+                .spawn_bundle(block(
+                    Vec2::new(middle, bar_height),
+                    Vec2::new(36.0, 8.0),
+                    BAR_COLOR,
+                ))
+                .insert(JumpBar(index));
+        }
+        ObstacleKind::Tire => {
+            let tire_height = class.tire_height();
+            let post = tire_height - TIRE_SIZE / 2.0;
+            parent.spawn_bundle(block(
+                Vec2::new(middle, post / 2.0),
+                Vec2::new(10.0, post),
+                POST_COLOR,
+            ));
+            parent.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(TIRE_SIZE)),
+                    ..default()
+                },
+                texture: asset_server.load("sprites/tire.png"),
+                transform: Transform::from_xyz(middle, tire_height, 0.0),
+                ..default()
+            });
+        }
+        ObstacleKind::Tunnel => {
+            parent.spawn_bundle(block(
+                Vec2::new(middle, TUNNEL_HEIGHT / 2.0),
+                Vec2::new(length, TUNNEL_HEIGHT),
+                TUNNEL_COLOR,
+            ));
+            for x in [5.0, length - 5.0] {
+                parent.spawn_bundle(block(
+                    Vec2::new(x, TUNNEL_HEIGHT / 2.0),
+                    Vec2::new(10.0, TUNNEL_HEIGHT + 8.0),
+                    TUNNEL_RIM_COLOR,
+                ));
+            }
+        }
+        ObstacleKind::Weave => {
+            parent.spawn_bundle(block(
+                Vec2::new(middle, 3.0),
+                Vec2::new(length, 6.0),
+                SUPPORT_COLOR,
+            ));
+            for pole in 0..WEAVE_POLES {
+                let color = if pole % 2 == 0 { POST_COLOR } else { BAR_COLOR };
+                parent.spawn_bundle(block(
+                    Vec2::new(pole_x(pole), WEAVE_HEIGHT / 2.0),
+                    Vec2::new(6.0, WEAVE_HEIGHT),
+                    color,
+                ));
+            }
+        }
+        ObstacleKind::AFrame | ObstacleKind::DogWalk => {
+            // Corners of the top, the first and last plank are the contact zones
+            let corners: Vec<f32> = match kind {
+                ObstacleKind::AFrame => vec![
+                    0.0,
+                    CONTACT_LENGTH,
+                    AFRAME_RAMP,
+                    length - CONTACT_LENGTH,
+                    length,
+                ],
+                _ => vec![
+                    0.0,
+                    CONTACT_LENGTH,
+                    DOG_WALK_RAMP,
+                    DOG_WALK_RAMP + DOG_WALK_PLANK,
+                    length - CONTACT_LENGTH,
+                    length,
+                ],
+            };
+            let top = |x: f32| Vec2::new(x, kind.surface(x, 0.0).unwrap_or_default());
+            for (index, pair) in corners.windows(2).enumerate() {
+                let color = if index == 0 || index == corners.len() - 2 {
+                    CONTACT_COLOR
+                } else {
+                    SURFACE_COLOR
+                };
+                parent.spawn_bundle(plank(top(pair[0]), top(pair[1]), color));
+            }
+            if kind == ObstacleKind::DogWalk {
+                for x in [DOG_WALK_RAMP, DOG_WALK_RAMP + DOG_WALK_PLANK] {
+                    parent.spawn_bundle(block(
+                        Vec2::new(x, DOG_WALK_HEIGHT / 2.0),
+                        Vec2::new(8.0, DOG_WALK_HEIGHT),
+                        SUPPORT_COLOR,
+                    ));
+                }
+            }
+        }
+        ObstacleKind::Seesaw => {
+            parent.spawn_bundle(block(
+                Vec2::new(middle, SEESAW_PIVOT / 2.0),
+                Vec2::new(14.0, SEESAW_PIVOT),
+                SUPPORT_COLOR,
+            ));
+            // Along the board, which is longer than the ground it covers
+            let board = 2.0 * Vec2::new(middle, SEESAW_PIVOT).length();
+            let contact = CONTACT_LENGTH * board / length;
+            let half = board / 2.0;
+            parent
+                .spawn_bundle((
+                    Transform::from_xyz(middle, SEESAW_PIVOT, 0.0),
+                    GlobalTransform::default(),
+                ))
+                .insert(SeesawBoard(index))
+                .with_children(|board| {
+                    for (from, to, color) in [
+                        (-half, -half + contact, CONTACT_COLOR),
+                        (-half + contact, half - contact, SURFACE_COLOR),
+                        (half - contact, half, CONTACT_COLOR),
+                    ] {
+                        board.spawn_bundle(plank(Vec2::new(from, 0.0), Vec2::new(to, 0.0), color));
+                    }
+                });
+        }
+    }
+}
+
+fn run_course(
+    player_input: Res<PlayerInput>,
+    run: Option<ResMut<CourseRun>>,
+    mut sounds: EventWriter<PlaySound>,
+    mut obstacle_events: EventWriter<ObstacleEvent>,
+) {
+    let mut run = match run {
+        Some(run) => run,
+        None => return,
+    };
+    let (jumped, done) = run.step(player_input.flap);
+    if jumped {
+        sounds.send(PlaySound("dog_jump"));
+    }
+    for (index, outcome, call) in done {
+        obstacle_events.send(ObstacleEvent {
+            kind: run.course.obstacles[index].kind,
+            outcome,
+            call,
+            position: Vec3::new(
+                DOG_X,
+                GROUND_Y + run.height + DOG_SIZE * run.class.scale(),
+                0.0,
+            ),
+        });
+    }
+}
+
+fn finish_course(run: Option<Res<CourseRun>>, mut state: ResMut<State<AppState>>) {
+    if let Some(run) = run {
+        if run.over() {
+            let _ = state.set(AppState::GameOver);
+        }
+    }
+}
+
+fn scroll_course(run: Option<Res<CourseRun>>, mut pieces: Query<(&CoursePiece, &mut Transform)>) {
+    let run = match run {
+        Some(run) => run,
+        None => return,
+    };
+    for (piece, mut transform) in pieces.iter_mut() {
+        piece.scroll(&mut transform, run.distance);
+    }
+}
+
+// Tips seesaws and drops knocked bars
+fn show_obstacles(
+    run: Option<Res<CourseRun>>,
+    mut boards: Query<(&SeesawBoard, &mut Transform), Without<JumpBar>>,
+    mut bars: Query<(&JumpBar, &mut Transform), Without<SeesawBoard>>,
+) {
+    let run = match run {
+        Some(run) => run,
+        None => return,
+    };
+    for (board, mut transform) in boards.iter_mut() {
+        let slope = -run.obstacles[board.0].tilt * SEESAW_PIVOT / (SEESAW_LENGTH / 2.0);
+        transform.rotation = Quat::from_rotation_z(slope.atan());
+    }
+    for (bar, mut transform) in bars.iter_mut() {
+        if run.obstacles[bar.0].outcome == Some(Outcome::Knocked) {
+            transform.translation.y = PLANK_THICKNESS / 2.0;
+        }
+    }
+}
+
+fn place_dog(run: Option<Res<CourseRun>>, mut dogs: Query<&mut Transform, With<CourseDog>>) {
+    let run = match run {
+        Some(run) => run,
+        None => return,
+    };
+    for mut transform in dogs.iter_mut() {
+        transform.translation.y = GROUND_Y + run.height + PAWS_OFFSET * run.class.scale();
+        transform.translation.z = if run.dog_behind() {
+            DOG_BEHIND_Z
+        } else {
+            DOG_Z
+        };
+        transform.rotation = Quat::from_rotation_z(run.dog_angle());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OBSTACLE_X: f32 = 200.0;
+
+    fn run_over(kind: ObstacleKind) -> CourseRun {
+        let course = Course {
+            name: "Test".to_string(),
+            obstacles: vec![ObstacleDef {
+                number: 1,
+                kind,
+                x: OBSTACLE_X,
+            }],
+            ..default()
+        };
+        CourseRun::new(course, DogClass::Medium)
+    }
+
+    // Puts the dog `offset` from the start of the obstacle. Up in the air it keeps
+    // its height for the next tick.
+    fn place(run: &mut CourseRun, offset: f32, height: f32, airborne: bool) {
+        run.distance = OBSTACLE_X + offset;
+        run.height = height;
+        run.airborne = airborne;
+        run.velocity = if airborne { GRAVITY } else { 0.0 };
+    }
+
+    // Steps the dog on until it is done with the obstacle
+    fn finish(run: &mut CourseRun, space: impl Fn(&CourseRun) -> bool) -> Outcome {
+        for _ in 0..1000 {
+            let space = space(run);
+            if let Some((_, outcome, _)) = run.step(space).1.first() {
+                return *outcome;
+            }
+        }
+        panic!("The dog never got done with the obstacle");
+    }
+
+    // Just before the middle of a jump or the tire
+    fn before_middle(kind: ObstacleKind) -> f32 {
+        kind.length() / 2.0 - 1.0
+    }
+
+    #[test]
+    fn jump_over_the_bar_is_clean() {
+        let mut run = run_over(ObstacleKind::Jump);
+        let height = run.class.bar_height() + 5.0;
+        place(&mut run, before_middle(ObstacleKind::Jump), height, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn jump_under_the_bar_knocks_it() {
+        let mut run = run_over(ObstacleKind::Jump);
+        let height = run.class.bar_height() - 10.0;
+        place(&mut run, before_middle(ObstacleKind::Jump), height, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Knocked);
+    }
+
+    #[test]
+    fn jump_run_under_is_run_past() {
+        let mut run = run_over(ObstacleKind::Jump);
+        place(&mut run, before_middle(ObstacleKind::Jump), 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::RunPast);
+    }
+
+    #[test]
+    fn tire_through_the_opening_is_clean() {
+        let mut run = run_over(ObstacleKind::Tire);
+        let height = run.class.tire_height() - TIRE_RADIUS + 5.0;
+        place(&mut run, before_middle(ObstacleKind::Tire), height, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn tire_over_the_top_is_knocked() {
+        let mut run = run_over(ObstacleKind::Tire);
+        let height = run.class.tire_height() + TIRE_RADIUS;
+        place(&mut run, before_middle(ObstacleKind::Tire), height, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Knocked);
+    }
+
+    #[test]
+    fn tire_run_under_is_run_past() {
+        let mut run = run_over(ObstacleKind::Tire);
+        place(&mut run, before_middle(ObstacleKind::Tire), 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::RunPast);
+    }
+
+    #[test]
+    fn tunnel_run_through_is_clean() {
+        let mut run = run_over(ObstacleKind::Tunnel);
+        place(&mut run, -10.0, 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn tunnel_jumped_over_is_run_past() {
+        let mut run = run_over(ObstacleKind::Tunnel);
+        place(&mut run, -1.0, 50.0, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::RunPast);
+    }
+
+    // Space once for every pole, in the middle of its window
+    fn weave_in_time(run: &CourseRun) -> bool {
+        let state = &run.obstacles[0];
+        let offset = run.distance + run.speed() - OBSTACLE_X;
+        run.inside == Some(0)
+            && !state.pole_taken
+            && (offset - pole_x(state.pole)).abs() <= WEAVE_WINDOW / 2.0
+    }
+
+    #[test]
+    fn weave_every_pole_is_clean() {
+        let mut run = run_over(ObstacleKind::Weave);
+        place(&mut run, 0.0, 0.0, false);
+        assert_eq!(finish(&mut run, weave_in_time), Outcome::Clean);
+    }
+
+    #[test]
+    fn weave_without_space_misses_poles() {
+        let mut run = run_over(ObstacleKind::Weave);
+        place(&mut run, 0.0, 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::MissedPole);
+    }
+
+    #[test]
+    fn weave_jumped_into_is_run_past() {
+        let mut run = run_over(ObstacleKind::Weave);
+        place(&mut run, pole_x(0) - WEAVE_WINDOW - 1.0, 50.0, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::RunPast);
+    }
+
+    #[test]
+    fn a_frame_walked_over_is_clean() {
+        let mut run = run_over(ObstacleKind::AFrame);
+        place(&mut run, -10.0, 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn a_frame_got_on_past_the_contact_misses_it() {
+        let mut run = run_over(ObstacleKind::AFrame);
+        let offset = CONTACT_LENGTH + 10.0;
+        let height = ObstacleKind::AFrame.surface(offset, 0.0).unwrap();
+        place(&mut run, offset, height, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::MissedContact);
+    }
+
+    #[test]
+    fn a_frame_jumped_over_is_run_past() {
+        let mut run = run_over(ObstacleKind::AFrame);
+        let length = ObstacleKind::AFrame.length();
+        place(&mut run, length - 1.0, 50.0, true);
+        assert_eq!(finish(&mut run, |_| false), Outcome::RunPast);
+    }
+
+    #[test]
+    fn dog_walk_walked_over_is_clean() {
+        let mut run = run_over(ObstacleKind::DogWalk);
+        place(&mut run, -10.0, 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn dog_walk_got_on_past_the_contact_misses_it() {
+        let mut run = run_over(ObstacleKind::DogWalk);
+        let offset = CONTACT_LENGTH + 10.0;
+        let height = ObstacleKind::DogWalk.surface(offset, 0.0).unwrap();
+        place(&mut run, offset, height, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::MissedContact);
+    }
+
+    #[test]
+    fn seesaw_walked_over_is_clean() {
+        let mut run = run_over(ObstacleKind::Seesaw);
+        place(&mut run, -10.0, 0.0, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::Clean);
+    }
+
+    #[test]
+    fn seesaw_got_on_past_the_contact_misses_it() {
+        let mut run = run_over(ObstacleKind::Seesaw);
+        let offset = CONTACT_LENGTH + 10.0;
+        // Not tipped yet
+        let height = ObstacleKind::Seesaw.surface(offset, -1.0).unwrap();
+        place(&mut run, offset, height, false);
+        assert_eq!(finish(&mut run, |_| false), Outcome::MissedContact);
+    }
+
+    #[test]
+    fn refusal_goes_back_before_the_obstacle() {
+        let mut run = run_over(ObstacleKind::Jump);
+        place(&mut run, before_middle(ObstacleKind::Jump), 0.0, false);
+        let call = loop {
+            if let Some((_, _, call)) = run.step(false).1.first() {
+                break *call;
+            }
+        };
+        assert_eq!(call, Call::Refusal);
+        assert_eq!(run.distance, OBSTACLE_X - RETRY_DISTANCE);
+        assert_eq!(run.height, 0.0);
+        assert!(!run.airborne);
+        assert!(run.obstacles[0].outcome.is_none());
+    }
+
+    #[test]
+    fn numbers_out_of_order_are_a_problem() {
+        let obstacle = |number, x| ObstacleDef {
+            number,
+            kind: ObstacleKind::Jump,
+            x,
+        };
+        let mut course = Course {
+            obstacles: vec![obstacle(1, 100.0), obstacle(0, 200.0), obstacle(2, 300.0)],
+            ..default()
+        };
+        assert!(course.problems().is_empty());
+        course.obstacles.push(obstacle(4, 400.0));
+        course.obstacles.push(obstacle(3, 500.0));
+        assert_eq!(
+            course.problems(),
+            vec!["The 4 Jump stands before the 3 Jump".to_string()]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Game data files under `assets/data`, read once at startup, agility courses under
+//! `assets/courses` and player files under `save`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::asset::FileAssetIo;
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub const SAVE_DIR: &str = "save";
+pub const COURSES_DIR: &str = "assets/courses";
+
+// Falls back to the built-in defaults when the file is missing or malformed
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    read(FileAssetIo::get_root_path().join("assets/data").join(name))
+}
+
+// Courses are read when a course run starts, so edits show up in the next run
+pub fn load_course<T: DeserializeOwned + Default>(name: &str) -> T {
+    read(FileAssetIo::get_root_path().join(COURSES_DIR).join(name))
+}
+
+fn save_path(name: &str) -> PathBuf {
+    FileAssetIo::get_root_path().join(SAVE_DIR).join(name)
+}
+
+// Like `load` but for files the game writes itself, so a missing file is expected
+pub fn load_saved<T: DeserializeOwned + Default>(name: &str) -> T {
+    let path = save_path(name);
+    if !path.exists() {
+        return T::default();
+    }
+    read(path)
+}
+
+pub fn store<T: Serialize>(name: &str, value: &T) {
+    write(save_path(name), value, PrettyConfig::default());
+}
+
+// Files for other programs, like exported standings. Returns whether the file
+// was written.
+pub fn store_text(name: &str, text: &str) -> bool {
+    let path = save_path(name);
+    if let Err(e) = fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, text))
+    {
+        error!("Could not write {}: {}", path.display(), e);
+        return false;
+    }
+    true
+}
+
+// Courses are shared and edited by hand too, so one obstacle per line. Returns
+// whether the file was written.
+pub fn store_course<T: Serialize>(name: &str, value: &T) -> bool {
+    let path = FileAssetIo::get_root_path().join(COURSES_DIR).join(name);
+    write(path, value, PrettyConfig::default().depth_limit(2))
+}
+
+// File names of the courses there are, sorted
+pub fn course_files() -> Vec<String> {
+    let dir = FileAssetIo::get_root_path().join(COURSES_DIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Could not list {}: {}", dir.display(), e);
+            return vec![];
+        }
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".ron"))
+        .collect();
+    names.sort();
+    names
+}
+
+fn write<T: Serialize>(path: PathBuf, value: &T, config: PrettyConfig) -> bool {
+    let text = match ron::ser::to_string_pretty(value, config) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("Could not serialize {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, text))
+    {
+        error!("Could not write {}: {}", path.display(), e);
+        return false;
+    }
+    true
+}
+
+fn read<T: DeserializeOwned + Default>(path: PathBuf) -> T {
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Could not read {}: {}", path.display(), e);
+            return T::default();
+        }
+    };
+    ron::from_str(&text).unwrap_or_else(|e| {
+        error!("Could not parse {}: {}", path.display(), e);
+        T::default()
+    })
+}
@@ -0,0 +1,439 @@
+//! Course designer, opened from the main menu. The course scrolls past a cursor
+//! line and everything is done with the keyboard: obstacles are added at the cursor
+//! and numbered, moved and removed there, the finish line goes where the cursor is
+//! and the standard course time is set up and down. Courses are saved as RON files
+//! under `assets/courses`, the same files course runs are read from, so they can be
+//! passed around. Gaps in the numbering, overlapping obstacles and the like are
+//! listed as they come up, and a course with problems isn't saved.
+
+use bevy::prelude::*;
+
+use crate::audio::PlaySound;
+use crate::course::{
+    spawn_course, Course, CoursePiece, CourseRun, ObstacleDef, ObstacleKind, SelectedCourse, DOG_X,
+    GROUND_Y,
+};
+use crate::settings::Settings;
+use crate::{data, AppState, GameMode};
+
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+// Pixels per second, four times that with Shift held
+const CURSOR_SPEED: f32 = 500.0;
+const FAST_FACTOR: f32 = 4.0;
+// Obstacles, the finish line and the cursor snap to this
+const GRID: f32 = 10.0;
+// How far past the finish line the cursor goes, to make the course longer
+const PAST_FINISH: f32 = 1000.0;
+const NEW_FINISH: f32 = 2000.0;
+const NEW_STANDARD_TIME: f32 = 30.0;
+const CURSOR_HEIGHT: f32 = 320.0;
+const CURSOR_Z: f32 = 20.0;
+const PANEL_MARGIN: f32 = 10.0;
+const CURSOR_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const INFO_COLOR: Color = Color::WHITE;
+const PROBLEM_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+const HELP_COLOR: Color = Color::rgb(0.75, 0.75, 0.75);
+const HELP: [&str; 3] = [
+    "Left/Right move, Shift faster    1-7 add Jump, Tire, Tunnel, Weave poles, A-frame, Dog walk, Seesaw",
+    "Up/Down renumber    ,/. nudge    Delete remove    R number in order    F finish here    -/+ standard time",
+    "S save    Enter save and run    Tab next course    N new course    Esc main menu",
+];
+
+// Start: --- Components
+// Everything the designer spawns
+#[derive(Component)]
+struct DesignerEntity;
+
+// The pieces of the course, spawned again whenever it changes
+#[derive(Component)]
+struct DesignPiece;
+
+#[derive(Component, Clone, Copy)]
+enum PanelText {
+    Info,
+    Problems,
+    Help,
+}
+// End: --- Components
+
+// Start: --- Resources
+// The course being designed, kept between visits to the designer
+#[derive(Default)]
+struct Design {
+    // Under `assets/courses`, empty until a course is opened
+    file: String,
+    course: Course,
+    // Along the course
+    cursor: f32,
+    // What the last save or load did
+    message: String,
+    // The course pieces on screen are out of date
+    rebuild: bool,
+}
+
+impl Design {
+    fn open(&mut self, file: &str) {
+        self.file = file.to_string();
+        self.course = Course::load(file);
+        self.cursor = 0.0;
+        self.message = format!("Opened {}", file);
+        self.rebuild = true;
+    }
+
+    fn new_course(&mut self) {
+        let files = data::course_files();
+        let number = (1..)
+            .find(|number| !files.contains(&format!("custom-{}.ron", number)))
+            .unwrap_or(1);
+        self.file = format!("custom-{}.ron", number);
+        self.course = Course {
+            name: format!("Custom {}", number),
+            obstacles: vec![],
+            finish: NEW_FINISH,
+            standard_time: NEW_STANDARD_TIME,
+        };
+        self.cursor = 0.0;
+        self.message = format!("New course {}", self.file);
+        self.rebuild = true;
+    }
+
+    // Only a course without problems is saved. Returns whether it was.
+    fn save(&mut self, selected: &mut SelectedCourse) -> bool {
+        if !self.course.problems().is_empty() {
+            self.message = "Not saved, fix the problems first".to_string();
+            return false;
+        }
+        if !data::store_course(&self.file, &self.course) {
+            self.message = format!("Could not save {}", self.file);
+            return false;
+        }
+        selected.0 = self.file.clone();
+        self.message = format!("Saved {}", self.file);
+        true
+    }
+
+    // The obstacle the cursor is on, the one that starts last if it is on several
+    fn here(&self) -> Option<usize> {
+        self.course
+            .obstacles
+            .iter()
+            .rposition(|def| (def.x..=def.end()).contains(&self.cursor))
+    }
+
+    fn add(&mut self, kind: ObstacleKind) {
+        self.course.obstacles.push(ObstacleDef {
+            number: self.course.last_number() + 1,
+            kind,
+            x: snap(self.cursor),
+        });
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.course.sort();
+        self.message.clear();
+        self.rebuild = true;
+    }
+
+    fn info(&self) -> String {
+        let here = match self.here() {
+            Some(index) => {
+                let def = &self.course.obstacles[index];
+                format!("{} at {:.0}", def.label(), def.x)
+            }
+            None => "nothing".to_string(),
+        };
+        format!(
+            "{} ({})\nStandard time: {:.0} s  Finish: {:.0}  Cursor: {:.0}  Here: {}\n{}",
+            self.course.name,
+            self.file,
+            self.course.standard_time,
+            self.course.finish,
+            snap(self.cursor),
+            here,
+            self.message
+        )
+    }
+}
+// End: --- Resources
+
+pub struct DesignerPlugin;
+
+impl Plugin for DesignerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Design>()
+            .add_system_set(SystemSet::on_enter(AppState::Designer).with_system(open_designer))
+            .add_system_set(SystemSet::on_exit(AppState::Designer).with_system(close_designer))
+            .add_system_set(
+                SystemSet::on_update(AppState::Designer)
+                    .with_system(designer_input)
+                    .with_system(rebuild_course.after(designer_input))
+                    .with_system(scroll_design.after(designer_input))
+                    .with_system(update_panel.after(designer_input)),
+            );
+    }
+}
+
+fn snap(x: f32) -> f32 {
+    (x / GRID).round() * GRID
+}
+
+fn panel_text(font: &Handle<Font>, font_size: f32, color: Color) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+            default(),
+        ),
+        ..default()
+    }
+}
+
+fn open_designer(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut design: ResMut<Design>,
+    selected: Res<SelectedCourse>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+) {
+    // The Return that opened the designer would otherwise start a run straight away
+    keyboard_input.clear();
+    // Left over from the last course run, it would scroll the designer's pieces
+    commands.remove_resource::<CourseRun>();
+    if design.file.is_empty() {
+        design.open(&selected.0);
+    }
+    design.rebuild = true;
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: CURSOR_COLOR,
+                custom_size: Some(Vec2::new(3.0, CURSOR_HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_xyz(DOG_X, GROUND_Y + CURSOR_HEIGHT / 2.0, CURSOR_Z),
+            ..default()
+        })
+        .insert(DesignerEntity);
+
+    let font = asset_server.load(FONT);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                // Top to bottom
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(PANEL_MARGIN)),
+                ..default()
+            },
+            color: UiColor(Color::NONE),
+            ..default()
+        })
+        .insert(DesignerEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(panel_text(&font, 24.0, INFO_COLOR))
+                .insert(PanelText::Info);
+            parent
+                .spawn_bundle(panel_text(&font, 20.0, PROBLEM_COLOR))
+                .insert(PanelText::Problems);
+            parent
+                .spawn_bundle(panel_text(&font, 16.0, HELP_COLOR))
+                .insert(PanelText::Help);
+        });
+}
+
+fn close_designer(mut commands: Commands, query: Query<Entity, With<DesignerEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+const KIND_KEYS: [KeyCode; 7] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+];
+
+fn designer_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut design: ResMut<Design>,
+    mut selected: ResMut<SelectedCourse>,
+    mut mode: ResMut<GameMode>,
+    mut state: ResMut<State<AppState>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let fast = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let speed = CURSOR_SPEED * if fast { FAST_FACTOR } else { 1.0 };
+    let mut direction = 0.0;
+    if keyboard_input.pressed(KeyCode::Left) {
+        direction -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::Right) {
+        direction += 1.0;
+    }
+    if direction != 0.0 {
+        let end = design.course.finish + PAST_FINISH;
+        design.cursor = (design.cursor + direction * speed * time.delta_seconds()).clamp(0.0, end);
+    }
+
+    for (key, kind) in KIND_KEYS.iter().zip(ObstacleKind::ALL) {
+        if keyboard_input.just_pressed(*key) {
+            design.add(kind);
+            sounds.send(PlaySound("menu_move"));
+        }
+    }
+
+    if let Some(index) = design.here() {
+        let mut edited = true;
+        if keyboard_input.just_pressed(KeyCode::Up) {
+            design.course.obstacles[index].number += 1;
+        } else if keyboard_input.just_pressed(KeyCode::Down) {
+            let def = &mut design.course.obstacles[index];
+            def.number = def.number.saturating_sub(1);
+        } else if keyboard_input.any_just_pressed([KeyCode::Comma, KeyCode::Period]) {
+            let step = if fast { 10.0 * GRID } else { GRID };
+            let step = if keyboard_input.just_pressed(KeyCode::Comma) {
+                -step
+            } else {
+                step
+            };
+            let def = &mut design.course.obstacles[index];
+            def.x = snap(def.x + step).max(0.0);
+            let x = def.x;
+            // The cursor goes along so it stays on the obstacle
+            design.cursor = (design.cursor + step).max(x);
+        } else if keyboard_input.any_just_pressed([KeyCode::Delete, KeyCode::Back]) {
+            design.course.obstacles.remove(index);
+        } else {
+            edited = false;
+        }
+        if edited {
+            design.changed();
+            sounds.send(PlaySound("menu_move"));
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::R) {
+        let mut number = 0;
+        for def in design.course.obstacles.iter_mut() {
+            if def.number != 0 {
+                number += 1;
+                def.number = number;
+            }
+        }
+        design.changed();
+    }
+    if keyboard_input.just_pressed(KeyCode::F) {
+        design.course.finish = snap(design.cursor);
+        design.changed();
+    }
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        design.course.standard_time = (design.course.standard_time - 1.0).max(1.0);
+        design.changed();
+    }
+    if keyboard_input.just_pressed(KeyCode::Equals) {
+        design.course.standard_time += 1.0;
+        design.changed();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::N) {
+        design.new_course();
+    }
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let files = data::course_files();
+        let next = files
+            .iter()
+            .position(|file| *file == design.file)
+            .map_or(0, |index| (index + 1) % files.len());
+        if let Some(file) = files.get(next) {
+            design.open(file);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::S) {
+        design.save(&mut selected);
+        sounds.send(PlaySound("menu_select"));
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        sounds.send(PlaySound("menu_select"));
+        if design.save(&mut selected) {
+            *mode = GameMode::Course;
+            let _ = state.set(AppState::InGame);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        sounds.send(PlaySound("menu_select"));
+        let _ = state.set(AppState::MainMenu);
+    }
+}
+
+fn rebuild_course(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    mut design: ResMut<Design>,
+    pieces: Query<Entity, With<DesignPiece>>,
+) {
+    if !design.rebuild {
+        return;
+    }
+    design.rebuild = false;
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    // Bars and tire as they will be set for the next run
+    let class = settings.dog_class;
+    for entity in spawn_course(
+        &mut commands,
+        &asset_server,
+        &design.course,
+        class,
+        design.cursor,
+    ) {
+        commands
+            .entity(entity)
+            .insert(DesignPiece)
+            .insert(DesignerEntity);
+    }
+}
+
+fn scroll_design(design: Res<Design>, mut pieces: Query<(&CoursePiece, &mut Transform)>) {
+    for (piece, mut transform) in pieces.iter_mut() {
+        piece.scroll(&mut transform, design.cursor);
+    }
+}
+
+fn update_panel(
+    design: Res<Design>,
+    mut query: Query<(&PanelText, &mut Text, ChangeTrackers<PanelText>)>,
+) {
+    for (kind, mut text, trackers) in query.iter_mut() {
+        // Checking the course every frame is wasted while nothing is edited
+        if !design.is_changed() && !trackers.is_added() {
+            continue;
+        }
+        let value = match kind {
+            PanelText::Info => design.info(),
+            PanelText::Problems => design.course.problems().join("\n"),
+            PanelText::Help => HELP.join("\n"),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
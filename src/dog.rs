@@ -0,0 +1,121 @@
+//! Dog size classes, the way agility splits dogs by height. Small dogs are smaller
+//! targets for the hawks and climb quicker under their balloons, large dogs jump
+//! higher and run faster. On the course the jump bars and the tire are set for the
+//! class and so is the standard course time. Medium dogs are the game as it was
+//! tuned, the other classes scale from there.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{PLAYER_HEIGHT, PLAYER_WIDTH, WINDOW_HEIGHT};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DogClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl Default for DogClass {
+    fn default() -> Self {
+        DogClass::Medium
+    }
+}
+
+impl DogClass {
+    pub const ALL: [DogClass; 3] = [DogClass::Small, DogClass::Medium, DogClass::Large];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DogClass::Small => "Small",
+            DogClass::Medium => "Medium",
+            DogClass::Large => "Large",
+        }
+    }
+
+    // The next class up or down, round at the ends
+    pub fn cycle(self, up: bool) -> Self {
+        let index = DogClass::ALL
+            .iter()
+            .position(|class| *class == self)
+            .unwrap_or(1);
+        let count = DogClass::ALL.len();
+        let step = if up { 1 } else { count - 1 };
+        DogClass::ALL[(index + step) % count]
+    }
+
+    // Of the sprite and the hitbox
+    pub fn scale(self) -> f32 {
+        match self {
+            DogClass::Small => 0.75,
+            DogClass::Medium => 1.0,
+            DogClass::Large => 1.2,
+        }
+    }
+
+    // Of the dog under its balloons
+    pub fn balloon_size(self) -> Vec2 {
+        Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT) * self.scale()
+    }
+
+    // Highest the dog gets under its balloons, standing on the ground it is as far
+    // below the middle of the screen
+    pub fn flight_top(self) -> f32 {
+        WINDOW_HEIGHT / 2.0 - self.balloon_size().y / 2.0
+    }
+
+    pub fn ground_y(self) -> f32 {
+        -self.flight_top()
+    }
+
+    // Pixels per tick the balloons climb while Space is held
+    pub fn lift(self) -> f32 {
+        match self {
+            DogClass::Small => 3.5,
+            DogClass::Medium => 3.0,
+            DogClass::Large => 2.5,
+        }
+    }
+
+    // Pixels per tick along the course
+    pub fn run_speed(self) -> f32 {
+        match self {
+            DogClass::Small => 1.8,
+            DogClass::Medium => 2.0,
+            DogClass::Large => 2.2,
+        }
+    }
+
+    // Pixels per tick up at the start of a jump
+    pub fn jump_speed(self) -> f32 {
+        match self {
+            DogClass::Small => 7.5,
+            DogClass::Medium => 9.0,
+            DogClass::Large => 10.5,
+        }
+    }
+
+    // Of the jump bar above the grass
+    pub fn bar_height(self) -> f32 {
+        match self {
+            DogClass::Small => 30.0,
+            DogClass::Medium => 45.0,
+            DogClass::Large => 60.0,
+        }
+    }
+
+    // Of the middle of the tire above the grass
+    pub fn tire_height(self) -> f32 {
+        match self {
+            DogClass::Small => 75.0,
+            DogClass::Medium => 95.0,
+            DogClass::Large => 135.0,
+        }
+    }
+
+    // Courses give the standard time for medium dogs, the others get it for their
+    // speed in whole seconds
+    pub fn standard_time(self, medium: f32) -> f32 {
+        (medium * DogClass::Medium.run_speed() / self.run_speed()).round()
+    }
+}
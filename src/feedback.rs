@@ -0,0 +1,107 @@
+//! Hit feedback: camera shake, hit-stop and a flashing dog while it is invulnerable.
+//! Each of them can be turned off in the settings.
+
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::{GameStep, HawkHitEvent, HitStop, Invulnerable, Player};
+
+// Trauma added per hit, the shake grows with the square of it
+const HIT_TRAUMA: f32 = 0.6;
+// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+const MAX_SHAKE_OFFSET: f32 = 18.0;
+const MAX_SHAKE_ANGLE: f32 = 0.04;
+const HIT_STOP_FRAMES: u32 = 6;
+// Ticks per on/off phase of the flash
+const FLASH_TICKS: u32 = 6;
+const FLASH_COLOR: Color = Color::rgba(1.0, 0.4, 0.4, 0.6);
+
+// Start: --- Components
+#[derive(Component, Default)]
+pub struct CameraShake {
+    trauma: f32,
+    elapsed: f32,
+}
+// End: --- Components
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(react_to_hits.after(GameStep::HawkCollision))
+            .add_system(shake_camera.after(react_to_hits))
+            .add_system(flash_invulnerable);
+    }
+}
+
+fn react_to_hits(
+    settings: Res<Settings>,
+    mut hit_stop: ResMut<HitStop>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+    mut cameras: Query<&mut CameraShake>,
+) {
+    if hawk_hits.iter().count() == 0 {
+        return;
+    }
+    if settings.screen_shake {
+        for mut shake in cameras.iter_mut() {
+            shake.trauma = (shake.trauma + HIT_TRAUMA).min(1.0);
+        }
+    }
+    if settings.hit_stop {
+        // Counted down once at the end of this frame already
+        hit_stop.0 = HIT_STOP_FRAMES + 1;
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut cameras: Query<(&mut CameraShake, &mut Transform)>,
+) {
+    for (mut shake, mut transform) in cameras.iter_mut() {
+        if !settings.screen_shake {
+            shake.trauma = 0.0;
+        }
+        shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+        shake.elapsed += time.delta_seconds();
+        let amount = shake.trauma * shake.trauma;
+        // Sums of sines at unrelated frequencies, smooth but irregular enough
+        let t = shake.elapsed;
+        let wobble = |a: f32, b: f32| ((t * a).sin() + (t * b + 1.3).sin()) / 2.0;
+        transform.translation.x = MAX_SHAKE_OFFSET * amount * wobble(37.0, 23.0);
+        transform.translation.y = MAX_SHAKE_OFFSET * amount * wobble(41.0, 29.0);
+        transform.rotation = Quat::from_rotation_z(MAX_SHAKE_ANGLE * amount * wobble(31.0, 19.0));
+    }
+}
+
+fn flash_invulnerable(
+    settings: Res<Settings>,
+    mut players: Query<
+        (
+            Option<&Invulnerable>,
+            Option<&mut Sprite>,
+            Option<&mut TextureAtlasSprite>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (invulnerable, sprite, atlas_sprite) in players.iter_mut() {
+        let flashing = match invulnerable {
+            Some(invulnerable) => settings.damage_flash && (invulnerable.0 / FLASH_TICKS) % 2 == 1,
+            None => false,
+        };
+        let color = if flashing { FLASH_COLOR } else { Color::WHITE };
+        if let Some(mut sprite) = sprite {
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+        if let Some(mut sprite) = atlas_sprite {
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
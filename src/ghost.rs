@@ -0,0 +1,158 @@
+//! Ghost racing: a translucent dog replaying the best earlier run with the same seed
+//! and dog size class.
+
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::dog::DogClass;
+use crate::replay::{config_hash, Replay, ReplayState, RunMode, REPLAY_DIR};
+use crate::{AppState, GameMode, GameStep, RunEntity, SimulationRunning, TotalPoints};
+
+// Start: --- Components
+#[derive(Component)]
+struct Ghost;
+
+#[derive(Component)]
+struct GhostDelta;
+// End: --- Components
+
+// Start: --- Resources
+struct GhostRun(Replay);
+// End: --- Resources
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::InGame).with_system(spawn_ghost.after(GameStep::NewRun)),
+        )
+        .add_system(
+            ghost_mover
+                .after(GameStep::Movement)
+                .before(GameStep::HawkSpawning)
+                .with_run_criteria(SimulationRunning),
+        )
+        .add_system(
+            update_ghost_delta
+                .after(GameStep::BoneCollision)
+                .before(GameStep::HawkSpawning)
+                .with_run_criteria(SimulationRunning),
+        );
+    }
+}
+
+// Best finished run in the replay directory for this seed, class and these game
+// settings
+fn best_replay(seed: u64, class: DogClass) -> Option<Replay> {
+    let entries = fs::read_dir(REPLAY_DIR).ok()?;
+    entries
+        .filter_map(|entry| Replay::load(&entry.ok()?.path()).ok())
+        .filter(|replay| replay.seed == seed && replay.config_hash == config_hash())
+        .filter(|replay| replay.dog == class)
+        .filter(|replay| !replay.track.is_empty())
+        .max_by_key(|replay| replay.points)
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<ReplayState>,
+    mode: Res<GameMode>,
+) {
+    // The ghost of the previous run doesn't carry over
+    commands.remove_resource::<GhostRun>();
+    if !matches!(state.mode, RunMode::Live { ghost: true, .. }) || *mode != GameMode::Balloon {
+        return;
+    }
+    let replay = match best_replay(state.replay.seed, state.replay.dog) {
+        Some(replay) => replay,
+        None => {
+            info!("No earlier run with seed {} to race against", state.replay.seed);
+            return;
+        }
+    };
+    info!("Racing a ghost with {} bones", replay.points);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(replay.dog.balloon_size()),
+                color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+                ..default()
+            },
+            texture: asset_server.load("sprites/dog-with-balloons-white.png"),
+            transform: Transform::from_xyz(-400., 0., 9.),
+            ..default()
+        })
+        .insert(Ghost)
+        .insert(RunEntity);
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                // The top of the screen belongs to the HUD
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::with_section(
+                "Ghost: 0",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+                },
+                default(),
+            ),
+            ..default()
+        })
+        .insert(GhostDelta)
+        .insert(RunEntity);
+
+    commands.insert_resource(GhostRun(replay));
+}
+
+fn ghost_mover(
+    ghost_run: Option<Res<GhostRun>>,
+    state: Res<ReplayState>,
+    mut ghost_query: Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+) {
+    let ghost_run = match ghost_run {
+        Some(ghost_run) => ghost_run,
+        None => return,
+    };
+    for (mut transform, mut visibility) in ghost_query.iter_mut() {
+        match ghost_run.0.track.get(state.tick as usize) {
+            Some(y) => transform.translation.y = *y as f32,
+            // The ghost's run is over
+            None => visibility.is_visible = false,
+        }
+    }
+}
+
+fn update_ghost_delta(
+    ghost_run: Option<Res<GhostRun>>,
+    state: Res<ReplayState>,
+    total_points: Res<TotalPoints>,
+    mut query: Query<&mut Text, With<GhostDelta>>,
+) {
+    let ghost_run = match ghost_run {
+        Some(ghost_run) => ghost_run,
+        None => return,
+    };
+    let delta = total_points.0 as i64 - ghost_run.0.points_at(state.tick) as i64;
+    for mut text in query.iter_mut() {
+        text.sections[0].value = match delta {
+            0 => "Ghost: even".to_string(),
+            d if d > 0 => format!("Ghost: {} ahead", d),
+            d => format!("Ghost: {} behind", -d),
+        };
+    }
+}
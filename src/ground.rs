@@ -0,0 +1,164 @@
+//! Scrolling ground built from grass tiles, defined in `assets/data/ground.ron`.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    data, ScenerySeed, SimulationRunning, GROUND_COLOR, WINDOW_HEIGHT, WINDOW_WIDTH, WORLD_SPEED,
+};
+
+const GROUND_RNG_STREAM: u64 = 1;
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct GroundConfig {
+    // Sprite sheet holding the grass tiles side by side
+    texture: String,
+    tile_size: (f32, f32),
+    columns: usize,
+    // Tiles laid out left to right, repeating
+    sequence: Vec<usize>,
+    // Tiles that now and then replace one from the sequence
+    variations: Vec<usize>,
+    variation_chance: f64,
+    y: f32,
+    z: f32,
+    // Height of the GROUND_COLOR strip behind the bottom of the grass
+    fill_height: f32,
+}
+
+impl Default for GroundConfig {
+    fn default() -> Self {
+        GroundConfig {
+            texture: "sprites/grass.png".to_string(),
+            tile_size: (320.0, 70.0),
+            columns: 4,
+            sequence: vec![0, 1, 2, 3],
+            variations: vec![],
+            variation_chance: 0.0,
+            y: -365.0,
+            z: 20.0,
+            fill_height: 35.0,
+        }
+    }
+}
+
+impl GroundConfig {
+    fn tile_index(&self, position: usize, rng: &mut StdRng) -> usize {
+        if !self.variations.is_empty() && rng.gen_bool(self.variation_chance.clamp(0.0, 1.0)) {
+            return *self.variations.choose(rng).unwrap();
+        }
+        self.sequence
+            .get(position % self.sequence.len().max(1))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+// End: --- Data
+
+// Start: --- Components
+#[derive(Component)]
+struct GroundTile;
+// End: --- Components
+
+// Start: --- Resources
+struct GroundStrip {
+    config: GroundConfig,
+    rng: StdRng,
+    // Position in the endless strip of the next tile to come in from the right
+    next: usize,
+    span: f32,
+}
+// End: --- Resources
+
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_ground)
+            .add_system(ground_scroll.with_run_criteria(SimulationRunning));
+    }
+}
+
+fn spawn_ground(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    scenery_seed: Res<ScenerySeed>,
+) {
+    let config: GroundConfig = data::load("ground.ron");
+    let mut rng = scenery_seed.rng(GROUND_RNG_STREAM);
+    let (width, height) = config.tile_size;
+    let columns = config.columns.max(1);
+    let rows = config
+        .sequence
+        .iter()
+        .chain(&config.variations)
+        .max()
+        .map_or(1, |last| last / columns + 1);
+    let atlas = TextureAtlas::from_grid(
+        asset_server.load(config.texture.as_str()),
+        Vec2::new(width, height),
+        columns,
+        rows,
+    );
+    let atlas = texture_atlases.add(atlas);
+
+    // Solid ground behind the lower half of the grass down to the window edge
+    let bottom = -WINDOW_HEIGHT / 2.0;
+    let fill_top = config.y - height / 2.0 + config.fill_height;
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: GROUND_COLOR,
+            custom_size: Some(Vec2::new(WINDOW_WIDTH, fill_top - bottom)),
+            ..default()
+        },
+        transform: Transform::from_xyz(0., (fill_top + bottom) / 2.0, config.z - 0.5),
+        ..default()
+    });
+
+    let copies = (WINDOW_WIDTH / width).ceil() as usize + 1;
+    let left = -WINDOW_WIDTH / 2.0 + width / 2.0;
+    for position in 0..copies {
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: atlas.clone(),
+                sprite: TextureAtlasSprite {
+                    index: config.tile_index(position, &mut rng),
+                    custom_size: Some(Vec2::new(width, height)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(left + position as f32 * width, config.y, config.z),
+                ..default()
+            })
+            .insert(GroundTile);
+    }
+    commands.insert_resource(GroundStrip {
+        config,
+        rng,
+        next: copies,
+        span: copies as f32 * width,
+    });
+}
+
+fn ground_scroll(
+    strip: Option<ResMut<GroundStrip>>,
+    mut tiles: Query<(&mut Transform, &mut TextureAtlasSprite), With<GroundTile>>,
+) {
+    let mut strip = match strip {
+        Some(strip) => strip,
+        None => return,
+    };
+    let wrap_at = -(WINDOW_WIDTH + strip.config.tile_size.0) / 2.0;
+    for (mut transform, mut sprite) in tiles.iter_mut() {
+        transform.translation.x -= WORLD_SPEED;
+        if transform.translation.x < wrap_at {
+            // Reuse the tile as the next one on the right
+            transform.translation.x += strip.span;
+            let strip = &mut *strip;
+            sprite.index = strip.config.tile_index(strip.next, &mut strip.rng);
+            strip.next += 1;
+        }
+    }
+}
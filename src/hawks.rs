@@ -0,0 +1,241 @@
+//! How hawks fly, defined in `assets/data/hawks.ron`. Every hawk picks a behaviour
+//! when it is spawned: straight, homing in on the dog, swooping down in an arc or
+//! circling before it attacks. Before a hawk starts its attack it telegraphs it for
+//! a moment so the player can react.
+//!
+//! Hawks are part of the simulation, so the file is included in the replay config
+//! hash like `bones.ron`.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::{data, WINDOW_WIDTH};
+
+pub const HAWKS_FILE: &str = "hawks.ron";
+
+// Start: --- Data
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Behaviour {
+    Straight,
+    // Follows the dog's height, turning at most `turn_rate` pixels per tick
+    Homing {
+        turn_rate: f32,
+    },
+    // Dives in an arc through where the dog was, starting `distance` pixels in
+    // front of it, `speed_up` times as fast
+    Swooping {
+        distance: f32,
+        speed_up: f32,
+    },
+    // Stops at `hover_x` to circle for `hover_ticks`, then dashes at the dog
+    Circling {
+        hover_x: f32,
+        radius: f32,
+        hover_ticks: u32,
+        attack_speed: f32,
+    },
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Behaviour::Straight
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BehaviourDef {
+    // Relative to the other weights
+    pub weight: u32,
+    pub behaviour: Behaviour,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HawkConfig {
+    // Ticks a hawk gives away its attack before it starts
+    pub telegraph_ticks: u32,
+    pub behaviours: Vec<BehaviourDef>,
+}
+
+impl Default for HawkConfig {
+    fn default() -> Self {
+        HawkConfig {
+            telegraph_ticks: 45,
+            behaviours: vec![BehaviourDef {
+                weight: 1,
+                behaviour: Behaviour::Straight,
+            }],
+        }
+    }
+}
+
+impl HawkConfig {
+    // Falls back to straight hawks only when the file has no behaviour with a weight
+    pub fn load() -> Self {
+        let config: HawkConfig = data::load(HAWKS_FILE);
+        if config.behaviours.iter().all(|def| def.weight == 0) {
+            warn!("No hawk behaviour with a weight in {}", HAWKS_FILE);
+            return HawkConfig::default();
+        }
+        config
+    }
+
+    // Picks the behaviour of a new hawk
+    pub fn roll(&self, rng: &mut StdRng) -> HawkAi {
+        let behaviour = self
+            .behaviours
+            .choose_weighted(rng, |def| def.weight)
+            .map(|def| def.behaviour)
+            .unwrap_or(Behaviour::Straight);
+        self.ai(behaviour)
+    }
+
+    pub fn ai(&self, behaviour: Behaviour) -> HawkAi {
+        HawkAi {
+            behaviour,
+            configured: behaviour,
+            telegraph_ticks: self.telegraph_ticks,
+            phase: Phase::Cruise,
+            ticks: 0,
+            origin: Vec2::ZERO,
+            target: Vec2::ZERO,
+        }
+    }
+}
+// End: --- Data
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    // Flying in from the right
+    Cruise,
+    // Circling hawks only
+    Hover,
+    // Giving the attack away
+    Telegraph,
+    Attack,
+}
+
+// Start: --- Components
+#[derive(Component)]
+pub struct HawkAi {
+    pub behaviour: Behaviour,
+    // What behaviour goes back to for the next pass, a swoop that came too late
+    // flies on straight
+    configured: Behaviour,
+    telegraph_ticks: u32,
+    pub phase: Phase,
+    // Ticks spent in the current phase
+    ticks: u32,
+    // Where the swoop started or the centre of the circle
+    origin: Vec2,
+    // Where the swoop goes through or the velocity of the dash
+    target: Vec2,
+}
+
+impl HawkAi {
+    fn enter(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.ticks = 0;
+    }
+
+    // Back to the start once the hawk came round again
+    pub fn reset(&mut self) {
+        self.behaviour = self.configured;
+        self.enter(Phase::Cruise);
+    }
+
+    // Moves the hawk by one tick. `dog` is the point of the dog hawks aim for.
+    pub fn step(&mut self, speed: f32, position: &mut Vec3, dog: Vec2) {
+        self.ticks += 1;
+        let telegraph_over = self.phase == Phase::Telegraph && self.ticks >= self.telegraph_ticks;
+        match self.behaviour {
+            Behaviour::Straight => position.x -= speed,
+            Behaviour::Homing { turn_rate } => {
+                position.x -= speed;
+                match self.phase {
+                    // Once it is fully on screen
+                    Phase::Cruise if position.x < WINDOW_WIDTH / 2.0 - 40.0 => {
+                        self.enter(Phase::Telegraph)
+                    }
+                    _ if telegraph_over => self.enter(Phase::Attack),
+                    // Gives up once it passed the dog
+                    Phase::Attack if position.x > dog.x => {
+                        position.y += (dog.y - position.y).clamp(-turn_rate, turn_rate);
+                    }
+                    _ => {}
+                }
+            }
+            Behaviour::Swooping { distance, speed_up } => match self.phase {
+                Phase::Attack => {
+                    position.x -= speed * speed_up;
+                    // Down to the dog and back up again, like half a circle
+                    let progress = (self.origin.x - position.x) / (self.origin.x - self.target.x);
+                    let lift = (progress.min(2.0) * FRAC_PI_2).sin();
+                    position.y = self.origin.y + (self.target.y - self.origin.y) * lift;
+                }
+                _ => {
+                    position.x -= speed;
+                    // Telegraphs so the dive starts `distance` in front of the dog
+                    let lead = speed * self.telegraph_ticks as f32;
+                    if self.phase == Phase::Cruise && position.x - dog.x < distance + lead {
+                        self.enter(Phase::Telegraph);
+                    } else if telegraph_over {
+                        self.enter(Phase::Attack);
+                        self.origin = position.truncate();
+                        self.target = dog;
+                        if self.origin.x <= self.target.x {
+                            // Too late to swoop, carry on straight
+                            self.behaviour = Behaviour::Straight;
+                        }
+                    }
+                }
+            },
+            Behaviour::Circling {
+                hover_x,
+                radius,
+                hover_ticks,
+                attack_speed,
+            } => match self.phase {
+                Phase::Cruise => {
+                    position.x -= speed;
+                    if position.x <= hover_x {
+                        self.enter(Phase::Hover);
+                        self.origin = position.truncate() - Vec2::new(radius, 0.0);
+                    }
+                }
+                Phase::Hover | Phase::Telegraph => {
+                    // Round at the speed it flew in with, starting where it stopped
+                    let angle = self.ticks_circling(hover_ticks) as f32 * speed / radius.max(1.0);
+                    let offset = Vec2::new(angle.cos(), angle.sin()) * radius;
+                    position.x = self.origin.x + offset.x;
+                    position.y = self.origin.y + offset.y;
+                    if self.phase == Phase::Hover && self.ticks >= hover_ticks {
+                        self.enter(Phase::Telegraph);
+                    } else if telegraph_over {
+                        self.enter(Phase::Attack);
+                        // Always some way forward, a hawk can't dash straight down
+                        let mut direction = (dog - position.truncate()).normalize_or_zero();
+                        direction.x = direction.x.min(-0.5);
+                        self.target = direction.normalize() * attack_speed;
+                    }
+                }
+                Phase::Attack => {
+                    position.x += self.target.x;
+                    position.y += self.target.y;
+                }
+            },
+        }
+    }
+
+    // Ticks since the hawk started circling, the telegraph carries on the circle
+    fn ticks_circling(&self, hover_ticks: u32) -> u32 {
+        match self.phase {
+            Phase::Telegraph => hover_ticks + self.ticks,
+            _ => self.ticks,
+        }
+    }
+}
+// End: --- Components
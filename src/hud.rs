@@ -0,0 +1,315 @@
+//! Heads-up display along the top of the screen: lives, time and distance on the
+//! left, score, bones, combo and power-ups on the right. Bone pickups float their
+//! points up from where the bone was, decoys say so. On a course the left shows the
+//! course, the time against the standard course time and the next obstacle and the
+//! right the faults and refusals, and every obstacle pops up how the judge called it.
+//!
+//! A text is only rewritten when the value behind it changed.
+
+use bevy::prelude::*;
+
+use crate::bones::BoneKind;
+use crate::course::{format_course_time, CourseRun, ObstacleEvent};
+use crate::judge::Call;
+use crate::replay::TICKS_PER_SECOND;
+use crate::{
+    AppState, BonePickupEvent, Combo, GameStep, Lives, PowerUps, RunEntity, RunTicks, Score,
+    TotalPoints, WORLD_SPEED,
+};
+
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+const PIXELS_PER_METRE: f32 = 40.0;
+const HUD_MARGIN: f32 = 10.0;
+const POPUP_SECONDS: f32 = 0.8;
+// Pixels per second
+const POPUP_RISE: f32 = 80.0;
+const POPUP_Z: f32 = 40.0;
+const POPUP_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const DECOY_COLOR: Color = Color::rgb(0.7, 0.66, 0.58);
+const FAULT_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+
+// Start: --- Components
+#[derive(Component, Clone, Copy)]
+enum HudText {
+    Lives,
+    Time,
+    Distance,
+    Score,
+    Bones,
+    Combo,
+    PowerUps,
+}
+
+#[derive(Component)]
+struct ScorePopup {
+    age: f32,
+}
+// End: --- Components
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(spawn_hud))
+            .add_system(update_hud.after(GameStep::HawkSpawning))
+            .add_system(spawn_score_popups.after(GameStep::BoneCollision))
+            .add_system(spawn_obstacle_popups)
+            .add_system(float_score_popups);
+    }
+}
+
+fn hud_column(align_items: AlignItems) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            // Top to bottom
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items,
+            ..default()
+        },
+        color: UiColor(Color::NONE),
+        ..default()
+    }
+}
+
+fn hud_text(font: &Handle<Font>, font_size: f32) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+            default(),
+        ),
+        ..default()
+    }
+}
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(FONT);
+    // A full width row with a column at either end, so it follows the window size
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::FlexStart,
+                padding: Rect::all(Val::Px(HUD_MARGIN)),
+                ..default()
+            },
+            color: UiColor(Color::NONE),
+            ..default()
+        })
+        .insert(RunEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(hud_column(AlignItems::FlexStart))
+                .with_children(|column| {
+                    for kind in [HudText::Lives, HudText::Time, HudText::Distance] {
+                        column.spawn_bundle(hud_text(&font, 26.0)).insert(kind);
+                    }
+                });
+            parent
+                .spawn_bundle(hud_column(AlignItems::FlexEnd))
+                .with_children(|column| {
+                    column
+                        .spawn_bundle(hud_text(&font, 30.0))
+                        .insert(HudText::Score);
+                    for kind in [HudText::Bones, HudText::Combo, HudText::PowerUps] {
+                        column.spawn_bundle(hud_text(&font, 24.0)).insert(kind);
+                    }
+                });
+        });
+}
+
+fn format_time(ticks: u32) -> String {
+    let seconds = (ticks as f64 / TICKS_PER_SECOND) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn update_hud(
+    course: Option<Res<CourseRun>>,
+    lives: Res<Lives>,
+    run_ticks: Res<RunTicks>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    combo: Res<Combo>,
+    power_ups: Res<PowerUps>,
+    mut query: Query<(&HudText, &mut Text, ChangeTrackers<HudText>)>,
+) {
+    if let Some(course) = course {
+        update_course_hud(&course, course.is_changed(), query);
+        return;
+    }
+    for (kind, mut text, trackers) in query.iter_mut() {
+        let changed = match kind {
+            HudText::Lives => lives.is_changed(),
+            HudText::Time | HudText::Distance => run_ticks.is_changed(),
+            HudText::Score => score.is_changed(),
+            HudText::Bones => total_points.is_changed(),
+            HudText::Combo => combo.is_changed(),
+            HudText::PowerUps => power_ups.is_changed(),
+        };
+        if !changed && !trackers.is_added() {
+            continue;
+        }
+        let value = match kind {
+            HudText::Lives => format!("Lives: {}", lives.0),
+            HudText::Time => format!("Time: {}", format_time(run_ticks.0)),
+            HudText::Distance => format!(
+                "Distance: {} m",
+                (run_ticks.0 as f32 * WORLD_SPEED / PIXELS_PER_METRE) as u32
+            ),
+            HudText::Score => format!("Score: {}", score.0),
+            HudText::Bones => format!("Bones: {}", total_points.0),
+            // Only worth showing once the combo pays off
+            HudText::Combo if combo.multiplier > 1 => {
+                format!("Combo {}  x{}", combo.chain, combo.multiplier)
+            }
+            HudText::Combo => String::new(),
+            HudText::PowerUps => power_ups
+                .0
+                .iter()
+                .map(|power_up| {
+                    let seconds = (power_up.ticks_left as f64 / TICKS_PER_SECOND).ceil();
+                    format!("{} {}s", power_up.name, seconds)
+                })
+                .collect::<Vec<_>>()
+                .join("  "),
+        };
+        // The time only changes every 60 ticks, don't lay out the same text again
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+// Same texts, what matters on a course
+fn update_course_hud(
+    course: &CourseRun,
+    changed: bool,
+    mut query: Query<(&HudText, &mut Text, ChangeTrackers<HudText>)>,
+) {
+    for (kind, mut text, trackers) in query.iter_mut() {
+        if !changed && !trackers.is_added() {
+            continue;
+        }
+        let value = match kind {
+            HudText::Lives => course.course.name.clone(),
+            HudText::Time => format!(
+                "Time: {}  SCT: {:.0} s",
+                format_course_time(course.time()),
+                course.judge.standard_time()
+            ),
+            HudText::Distance => match (course.judge.eliminated(), course.next_obstacle()) {
+                (Some(_), _) => "Eliminated".to_string(),
+                (None, Some(next)) => format!("Next: {} {}", next.number, next.kind.name()),
+                (None, None) => "Next: finish".to_string(),
+            },
+            HudText::Score => format!("Faults: {:.2}", course.judge.total_faults(course.time())),
+            HudText::Bones => format!("Refusals: {}", course.judge.refusals()),
+            HudText::Combo | HudText::PowerUps => String::new(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn spawn_score_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+) {
+    for pickup in bone_pickups.iter() {
+        // Bigger combos, bigger numbers
+        let font_size = 24.0 + 6.0 * pickup.points.saturating_sub(1).min(4) as f32;
+        let (value, color) = match pickup.kind {
+            BoneKind::Decoy => ("Decoy!".to_string(), DECOY_COLOR),
+            _ => (format!("+{}", pickup.points), POPUP_COLOR),
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size,
+                        color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_translation(pickup.position.truncate().extend(POPUP_Z)),
+                ..default()
+            })
+            .insert(ScorePopup { age: 0.0 })
+            .insert(RunEntity);
+    }
+}
+
+fn spawn_obstacle_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut obstacle_events: EventReader<ObstacleEvent>,
+) {
+    for event in obstacle_events.iter() {
+        let (value, color) = match event.call {
+            Call::Ignored => continue,
+            Call::Faults(0) => ("Clean".to_string(), POPUP_COLOR),
+            Call::Faults(faults) => (
+                format!("{} +{}", event.outcome.describe(event.kind), faults),
+                FAULT_COLOR,
+            ),
+            Call::Refusal => ("Refusal".to_string(), FAULT_COLOR),
+            Call::Eliminated => ("Eliminated".to_string(), FAULT_COLOR),
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: 30.0,
+                        color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_translation(event.position.truncate().extend(POPUP_Z)),
+                ..default()
+            })
+            .insert(ScorePopup { age: 0.0 })
+            .insert(RunEntity);
+    }
+}
+
+fn float_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(1.0 - popup.age / POPUP_SECONDS);
+    }
+}
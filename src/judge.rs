@@ -0,0 +1,281 @@
+//! Judging of course runs the way an agility judge does it. Knocked bars, missed
+//! contacts and missed weave poles are five faults each. Running past the next
+//! obstacle is a refusal, also five faults, and the dog goes back to try it again;
+//! the third refusal eliminates. So does taking an obstacle out of order, including
+//! the ones without a number that stand on the field but aren't part of the course.
+//! Every second over the standard course time is a time fault and a run over the
+//! maximum course time, half as long again, is eliminated.
+
+use crate::course::{format_course_time, Course, ObstacleDef, Outcome};
+use crate::replay::TICKS_PER_SECOND;
+
+const FAULTS_PER_MISTAKE: u32 = 5;
+const REFUSALS_ALLOWED: u32 = 2;
+// Of the standard course time
+const MAXIMUM_TIME_FACTOR: f32 = 1.5;
+
+// How the judge calls the dog being done with an obstacle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    // Taken in order with these faults, 0 when clean
+    Faults(u32),
+    // Ran past the next obstacle, the dog goes back for another try
+    Refusal,
+    // Ran past an obstacle that isn't the next one, which is fine
+    Ignored,
+    Eliminated,
+}
+
+// A line on the judge's sheet
+struct Mistake {
+    obstacle: ObstacleDef,
+    what: &'static str,
+}
+
+pub struct Judge {
+    standard_time: f32,
+    last_number: u32,
+    // Number of the obstacle that has to come next
+    next: u32,
+    refusals: u32,
+    mistakes: Vec<Mistake>,
+    eliminated: Option<String>,
+}
+
+impl Judge {
+    // With the standard course time for the dog's class
+    pub fn new(course: &Course, standard_time: f32) -> Self {
+        Judge {
+            standard_time,
+            last_number: course.last_number(),
+            next: 1,
+            refusals: 0,
+            mistakes: vec![],
+            eliminated: None,
+        }
+    }
+
+    pub fn next(&self) -> u32 {
+        self.next
+    }
+
+    pub fn standard_time(&self) -> f32 {
+        self.standard_time
+    }
+
+    pub fn eliminated(&self) -> Option<&str> {
+        self.eliminated.as_deref()
+    }
+
+    pub fn call(&mut self, obstacle: &ObstacleDef, outcome: Outcome) -> Call {
+        if self.eliminated.is_some() {
+            return Call::Ignored;
+        }
+        if obstacle.number != self.next || obstacle.number == 0 {
+            if outcome == Outcome::RunPast {
+                return Call::Ignored;
+            }
+            self.eliminate(format!("Wrong course: took the {}", obstacle.label()));
+            return Call::Eliminated;
+        }
+        let what = match outcome {
+            Outcome::Clean => {
+                self.next += 1;
+                return Call::Faults(0);
+            }
+            Outcome::RunPast => {
+                self.refusals += 1;
+                self.mistakes.push(Mistake {
+                    obstacle: *obstacle,
+                    what: "Refusal",
+                });
+                if self.refusals > REFUSALS_ALLOWED {
+                    self.eliminate("Three refusals".to_string());
+                    return Call::Eliminated;
+                }
+                return Call::Refusal;
+            }
+            outcome => outcome.describe(obstacle.kind),
+        };
+        self.next += 1;
+        self.mistakes.push(Mistake {
+            obstacle: *obstacle,
+            what,
+        });
+        Call::Faults(FAULTS_PER_MISTAKE)
+    }
+
+    // Checked every tick of the run
+    pub fn check_time(&mut self, ticks: u32) {
+        if self.eliminated.is_none() && seconds(ticks) > self.maximum_time() {
+            self.eliminate("Over the maximum course time".to_string());
+        }
+    }
+
+    // At the finish line, every obstacle has to be taken by then
+    pub fn finish(&mut self) {
+        if self.eliminated.is_none() && self.next <= self.last_number {
+            self.eliminate(format!("Obstacle {} not taken", self.next));
+        }
+    }
+
+    fn eliminate(&mut self, reason: String) {
+        self.eliminated = Some(reason);
+    }
+
+    fn maximum_time(&self) -> f32 {
+        self.standard_time * MAXIMUM_TIME_FACTOR
+    }
+
+    pub fn course_faults(&self) -> u32 {
+        self.mistakes.len() as u32 * FAULTS_PER_MISTAKE
+    }
+
+    pub fn time_faults(&self, ticks: u32) -> f32 {
+        (seconds(ticks) - self.standard_time).max(0.0)
+    }
+
+    pub fn total_faults(&self, ticks: u32) -> f32 {
+        self.course_faults() as f32 + self.time_faults(ticks)
+    }
+
+    pub fn refusals(&self) -> u32 {
+        self.refusals
+    }
+
+    // The judge's sheet for the end of a run
+    pub fn sheet(&self, ticks: u32, finished: bool) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .mistakes
+            .iter()
+            .map(|mistake| {
+                format!(
+                    "{}: {}, {} faults",
+                    mistake.obstacle.label(),
+                    mistake.what,
+                    FAULTS_PER_MISTAKE
+                )
+            })
+            .collect();
+        if lines.is_empty() && finished {
+            lines.push("No faults on the course".to_string());
+        }
+        lines.push(format!(
+            "Course faults: {}  Refusals: {}",
+            self.course_faults(),
+            self.refusals
+        ));
+        lines.push(format!(
+            "Time: {}  Standard time: {:.0} s  Time faults: {:.2}",
+            format_course_time(ticks),
+            self.standard_time,
+            self.time_faults(ticks)
+        ));
+        lines.push(match (&self.eliminated, finished) {
+            (Some(reason), _) => format!("Eliminated: {}", reason),
+            (None, false) => "Did not finish".to_string(),
+            (None, true) => {
+                let total = self.total_faults(ticks);
+                format!("Total: {:.2} faults  {}", total, qualification(total))
+            }
+        });
+        lines
+    }
+}
+
+fn seconds(ticks: u32) -> f32 {
+    (ticks as f64 / TICKS_PER_SECOND) as f32
+}
+
+// Grading of a clean finish by total faults
+fn qualification(total: f32) -> &'static str {
+    match total {
+        total if total < 6.0 => "Excellent",
+        total if total < 16.0 => "Very good",
+        total if total < 26.0 => "Good",
+        _ => "Not classified",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::course::ObstacleKind;
+
+    const STANDARD_TIME: f32 = 30.0;
+
+    fn obstacle(number: u32) -> ObstacleDef {
+        ObstacleDef {
+            number,
+            kind: ObstacleKind::Jump,
+            x: number as f32 * 100.0,
+        }
+    }
+
+    fn judge() -> Judge {
+        let course = Course {
+            obstacles: (1..=3).map(obstacle).collect(),
+            ..Course::default()
+        };
+        Judge::new(&course, STANDARD_TIME)
+    }
+
+    fn ticks(seconds: f32) -> u32 {
+        (seconds as f64 * TICKS_PER_SECOND) as u32
+    }
+
+    #[test]
+    fn third_refusal_eliminates() {
+        let mut judge = judge();
+        for _ in 0..REFUSALS_ALLOWED {
+            assert_eq!(judge.call(&obstacle(1), Outcome::RunPast), Call::Refusal);
+        }
+        assert!(judge.eliminated().is_none());
+        assert_eq!(judge.call(&obstacle(1), Outcome::RunPast), Call::Eliminated);
+        assert!(judge.eliminated().is_some());
+    }
+
+    #[test]
+    fn wrong_obstacle_eliminates() {
+        let mut judge = judge();
+        assert_eq!(judge.call(&obstacle(2), Outcome::Clean), Call::Eliminated);
+        assert!(judge.eliminated().is_some());
+    }
+
+    #[test]
+    fn unnumbered_obstacle_eliminates() {
+        let mut judge = judge();
+        assert_eq!(judge.call(&obstacle(1), Outcome::Clean), Call::Faults(0));
+        assert_eq!(judge.call(&obstacle(0), Outcome::Knocked), Call::Eliminated);
+        assert!(judge.eliminated().is_some());
+    }
+
+    #[test]
+    fn run_past_other_obstacles_is_ignored() {
+        let mut judge = judge();
+        assert_eq!(judge.call(&obstacle(2), Outcome::RunPast), Call::Ignored);
+        assert_eq!(judge.call(&obstacle(0), Outcome::RunPast), Call::Ignored);
+        assert_eq!(judge.refusals(), 0);
+        assert_eq!(judge.course_faults(), 0);
+        assert!(judge.eliminated().is_none());
+        assert_eq!(judge.next(), 1);
+    }
+
+    #[test]
+    fn time_faults_past_the_standard_time() {
+        let judge = judge();
+        assert_eq!(judge.time_faults(ticks(STANDARD_TIME - 5.0)), 0.0);
+        assert_eq!(judge.time_faults(ticks(STANDARD_TIME)), 0.0);
+        assert_eq!(judge.time_faults(ticks(STANDARD_TIME + 2.5)), 2.5);
+    }
+
+    #[test]
+    fn eliminated_over_the_maximum_time() {
+        let mut judge = judge();
+        let maximum = ticks(STANDARD_TIME * MAXIMUM_TIME_FACTOR);
+        judge.check_time(maximum);
+        assert!(judge.eliminated().is_none());
+        judge.check_time(maximum + 1);
+        assert!(judge.eliminated().is_some());
+    }
+}
@@ -0,0 +1,777 @@
+use bevy::{
+    ecs::{schedule::ShouldRun, system::EntityCommands},
+    prelude::*,
+    sprite::collide_aabb::{collide, Collision},
+};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use bones::{BoneKind, BoneLibrary};
+use dog::DogClass;
+use hawks::{HawkAi, HawkConfig};
+use waves::{WaveConfig, WaveDirector};
+
+mod achievements;
+mod animation;
+mod audio;
+mod bones;
+mod clouds;
+mod competition;
+mod course;
+mod data;
+mod designer;
+mod dog;
+mod feedback;
+mod ghost;
+mod ground;
+mod hawks;
+mod hud;
+mod judge;
+mod menu;
+mod parallax;
+mod particles;
+pub mod replay;
+mod settings;
+mod stats;
+mod telegraph;
+mod viewer;
+mod warnings;
+mod waves;
+
+pub use achievements::AchievementsPlugin;
+pub use animation::AnimationPlugin;
+pub use audio::SoundPlugin;
+pub use clouds::CloudPlugin;
+pub use competition::CompetitionPlugin;
+pub use course::CoursePlugin;
+pub use designer::DesignerPlugin;
+pub use feedback::FeedbackPlugin;
+pub use ghost::GhostPlugin;
+pub use ground::GroundPlugin;
+pub use hud::HudPlugin;
+pub use menu::MenuPlugin;
+pub use parallax::ParallaxPlugin;
+pub use particles::ParticlePlugin;
+pub use replay::{ReplayPlugin, RunArgs};
+pub use settings::SettingsPlugin;
+pub use stats::StatsPlugin;
+pub use telegraph::TelegraphPlugin;
+pub use viewer::ReplayViewerPlugin;
+pub use warnings::WarningPlugin;
+
+// Start: --- Resources
+// Bones picked up in the current run
+pub struct TotalPoints(pub u32);
+
+// Points scored in the current run, bones are worth more during a combo
+pub struct Score(pub u32);
+
+// What a run is: the endless balloon flight or a run over an agility course
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum GameMode {
+    Balloon,
+    Course,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Balloon
+    }
+}
+
+// Single source of randomness for the simulation, seeded per run so replays are exact
+struct GameRng(StdRng);
+
+// Seed for purely visual randomness, kept apart so it never changes the simulation.
+// Every user takes its own stream so the order systems run in doesn't matter.
+struct ScenerySeed(u64);
+
+impl ScenerySeed {
+    fn rng(&self, stream: u64) -> StdRng {
+        StdRng::seed_from_u64(self.0 ^ stream.wrapping_mul(0x9e3779b97f4a7c15))
+    }
+}
+
+// Player controls sampled once per tick, either from the keyboard or from a replay
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+struct PlayerInput {
+    flap: bool,
+}
+
+impl PlayerInput {
+    fn to_bits(self) -> u8 {
+        self.flap as u8
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        PlayerInput { flap: bits & 1 != 0 }
+    }
+}
+
+// Frames left in which the simulation is frozen after a hit. Frozen frames are not
+// ticks, so replays are the same with or without hit-stop.
+#[derive(Default)]
+struct HitStop(u32);
+
+// A hawk hit costs a life, the run is over when none are left
+struct Lives(u32);
+
+// Ticks simulated in the current run
+#[derive(Default)]
+struct RunTicks(u32);
+
+// Bones picked up in quick succession raise the score multiplier. The combo
+// breaks when the dog is hit or no bone follows within COMBO_TICKS.
+struct Combo {
+    chain: u32,
+    multiplier: u32,
+    ticks_left: u32,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            chain: 0,
+            multiplier: 1,
+            ticks_left: 0,
+        }
+    }
+}
+
+impl Combo {
+    // Extends the combo by one bone and returns what a bone of the given value is worth
+    fn pick_up(&mut self, value: u32) -> u32 {
+        self.chain += 1;
+        self.multiplier = (1 + (self.chain - 1) / BONES_PER_MULTIPLIER).min(MAX_MULTIPLIER);
+        self.ticks_left = COMBO_TICKS;
+        value * self.multiplier
+    }
+}
+
+struct PowerUp {
+    name: String,
+    ticks_left: u32,
+}
+
+// Power-ups running out in the current run, nothing hands them out yet
+#[derive(Default)]
+struct PowerUps(Vec<PowerUp>);
+// End: --- Resources
+
+// Start: --- Events
+struct BonePickupEvent {
+    position: Vec3,
+    kind: BoneKind,
+    points: u32,
+}
+
+struct HawkHitEvent {
+    hawk: Vec3,
+    player: Vec3,
+}
+
+// A hawk flew past the dog without hitting it
+struct HawkDodgedEvent;
+// End: --- Events
+
+// Menus sit on top of a run: Paused is pushed over InGame and Settings over the menu
+// it was opened from, Achievements and Stats over the main menu. A run ends by leaving
+// InGame, its world is cleared after GameOver. The course designer takes the place of
+// the main menu, CompetitionSetup goes over it and Standings follows the GameOver of
+// the last run of a competition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum AppState {
+    MainMenu,
+    InGame,
+    Paused,
+    Settings,
+    GameOver,
+    Achievements,
+    Stats,
+    Designer,
+    CompetitionSetup,
+    Standings,
+}
+
+// Run criteria for everything that advances the game by a tick. Only true once the
+// state transition is done, so the entities of a new run exist before its first tick.
+#[derive(RunCriteriaLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct SimulationRunning;
+
+#[derive(RunCriteriaLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct InGameUpdate;
+
+// SimulationRunning during balloon runs only, for the flying dog, bones and hawks
+#[derive(RunCriteriaLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct BalloonRunning;
+
+// Simulation steps run in this fixed order every tick. Every step that draws from
+// GameRng needs its own label, otherwise the scheduler may reorder them between runs.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+enum GameStep {
+    // Picks the seed when a run starts, before anything is spawned
+    NewRun,
+    Input,
+    Movement,
+    BoneMovement,
+    HawkMovement,
+    BoneCollision,
+    HawkCollision,
+    HawkSpawning,
+}
+
+// Start: --- Components
+#[derive(Component)]
+struct Player;
+
+#[derive(Component)]
+struct Bone {
+    kind: BoneKind,
+    // Index into the BoneLibrary
+    def: usize,
+    // Height the bone waves around, if its kind waves
+    base_y: f32,
+}
+
+// Up and Down Movement Capability
+#[derive(Component)]
+struct UpAndDown;
+
+// Back and Forth Movement Capability
+#[derive(Component)]
+struct BackAndForth;
+
+#[derive(Component)]
+struct Hawk(f32);
+
+// Part of a wave, flies off for good instead of coming round again
+#[derive(Component)]
+struct WaveHawk;
+
+// Whether the hawk hit the dog since it last came in from the right
+#[derive(Component, Default)]
+struct HawkPass {
+    hit_dog: bool,
+    // Where the hawk was on the last tick, none before its first one
+    prev_x: Option<f32>,
+}
+
+#[derive(Component)]
+struct Collidable;
+
+// Everything that belongs to one run and goes away with it
+#[derive(Component)]
+struct RunEntity;
+
+// Ticks left in which hawks can't hit the dog again
+#[derive(Component)]
+struct Invulnerable(u32);
+// End: --- Components
+pub const WINDOW_WIDTH: f32 = 1280.0;
+pub const WINDOW_HEIGHT: f32 = 800.0;
+const PLAYER_WIDTH: f32 = 120.0;
+const PLAYER_HEIGHT: f32 = 200.0;
+const BONE_WIDTH: f32 = 30.0;
+const BONE_HEIGHT: f32 = 15.0;
+const HAWK_SIZE: f32 = 60.0;
+// Hawks come in from far enough out to be warned about this long before they are on screen
+const HAWK_LEAD_TICKS: f32 = 90.0;
+
+pub const BACKGROUND_COLOR: Color = Color::rgb(0.3, 0.7, 1.0); // sky blue
+const GROUND_COLOR: Color = Color::rgb(0.48, 0.988, 0.75); // light green
+
+// Pixels per tick everything on the ground level moves towards the dog
+const WORLD_SPEED: f32 = 2.0;
+
+const INVULNERABLE_TICKS: u32 = 90;
+const STARTING_LIVES: u32 = 3;
+
+const COMBO_TICKS: u32 = 180;
+// Bones in a row needed for every step up of the multiplier
+const BONES_PER_MULTIPLIER: u32 = 2;
+const MAX_MULTIPLIER: u32 = 5;
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        // Replays are watched straight away, otherwise the game opens on the main menu
+        let start = if app.world.resource::<RunArgs>().replay.is_some() {
+            AppState::InGame
+        } else {
+            AppState::MainMenu
+        };
+        app.add_state(start)
+            .init_resource::<GameMode>()
+            // Of the current run, picked when it starts
+            .init_resource::<DogClass>()
+            .insert_resource(PlayerInput::default())
+            .init_resource::<HitStop>()
+            .insert_resource(Lives(STARTING_LIVES))
+            .init_resource::<RunTicks>()
+            .init_resource::<Combo>()
+            .init_resource::<PowerUps>()
+            .insert_resource(HawkConfig::load())
+            .insert_resource(WaveConfig::load())
+            .init_resource::<WaveDirector>()
+            .add_event::<BonePickupEvent>()
+            .add_event::<HawkHitEvent>()
+            .add_event::<HawkDodgedEvent>()
+            .add_startup_system(setup_game)
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame)
+                    .with_system(reset_run)
+                    .with_system(spawn_player.after(GameStep::NewRun))
+                    .with_system(bone_spawner.after(GameStep::NewRun)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(despawn_run))
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(despawn_run))
+            // Only there to be piped into SimulationRunning
+            .add_system_set(
+                SystemSet::new().with_run_criteria(State::on_update(AppState::InGame).label(InGameUpdate)),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(RunCriteria::pipe(InGameUpdate, hit_stop_over).label(SimulationRunning))
+                    .with_system(read_keyboard_input.label(GameStep::Input))
+                    //.with_system(back_and_forth_movement)
+                    .with_system(bone_mover.label(GameStep::BoneMovement).after(GameStep::Movement))
+                    .with_system(hawk_mover.label(GameStep::HawkMovement).after(GameStep::BoneMovement))
+                    .with_system(player_collide_with_bone.label(GameStep::BoneCollision).after(GameStep::HawkMovement))
+                    .with_system(player_collide_with_hawk.label(GameStep::HawkCollision).after(GameStep::BoneCollision))
+                    .with_system(wear_off_invulnerability.after(GameStep::HawkCollision))
+                    .with_system(count_dodges.after(GameStep::HawkCollision))
+                    .with_system(lose_lives.after(GameStep::HawkCollision))
+                    .with_system(update_combo.after(GameStep::HawkCollision))
+                    .with_system(advance_clock),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(RunCriteria::pipe(SimulationRunning, balloon_mode).label(BalloonRunning))
+                    .with_system(player_movement.label(GameStep::Movement).after(GameStep::Input))
+                    .with_system(hawk_spawner.label(GameStep::HawkSpawning).after(GameStep::HawkCollision)),
+            )
+            .add_system_to_stage(CoreStage::Last, count_down_hit_stop);
+    }
+}
+
+// Holds the simulation back during hit-stop, on top of the InGame state check
+fn hit_stop_over(In(should_run): In<ShouldRun>, hit_stop: Res<HitStop>) -> ShouldRun {
+    match should_run {
+        ShouldRun::YesAndCheckAgain if hit_stop.0 > 0 => ShouldRun::NoAndCheckAgain,
+        ShouldRun::Yes if hit_stop.0 > 0 => ShouldRun::No,
+        should_run => should_run,
+    }
+}
+
+fn balloon_mode(In(should_run): In<ShouldRun>, mode: Res<GameMode>) -> ShouldRun {
+    match should_run {
+        ShouldRun::YesAndCheckAgain if *mode != GameMode::Balloon => ShouldRun::NoAndCheckAgain,
+        ShouldRun::Yes if *mode != GameMode::Balloon => ShouldRun::No,
+        should_run => should_run,
+    }
+}
+
+fn count_down_hit_stop(mut hit_stop: ResMut<HitStop>) {
+    hit_stop.0 = hit_stop.0.saturating_sub(1);
+}
+
+fn setup_game(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d()) // needed for graphics
+        .insert(feedback::CameraShake::default());
+    commands.spawn_bundle(UiCameraBundle::default()); // needed for (text) ui
+    commands.insert_resource(TotalPoints(0));
+    commands.insert_resource(Score(0));
+    commands.insert_resource(BoneLibrary::load(&asset_server));
+}
+
+fn reset_run(
+    mut total_points: ResMut<TotalPoints>,
+    mut score: ResMut<Score>,
+    mut player_input: ResMut<PlayerInput>,
+    mut hit_stop: ResMut<HitStop>,
+    mut lives: ResMut<Lives>,
+    mut run_ticks: ResMut<RunTicks>,
+    mut combo: ResMut<Combo>,
+    mut power_ups: ResMut<PowerUps>,
+    mut waves: ResMut<WaveDirector>,
+) {
+    total_points.0 = 0;
+    score.0 = 0;
+    *player_input = PlayerInput::default();
+    hit_stop.0 = 0;
+    lives.0 = STARTING_LIVES;
+    run_ticks.0 = 0;
+    *combo = Combo::default();
+    power_ups.0.clear();
+    *waves = WaveDirector::default();
+}
+
+fn advance_clock(mut run_ticks: ResMut<RunTicks>, mut power_ups: ResMut<PowerUps>) {
+    run_ticks.0 += 1;
+    if !power_ups.0.is_empty() {
+        for power_up in power_ups.0.iter_mut() {
+            power_up.ticks_left = power_up.ticks_left.saturating_sub(1);
+        }
+        power_ups.0.retain(|power_up| power_up.ticks_left > 0);
+    }
+}
+
+fn despawn_run(mut commands: Commands, query: Query<Entity, With<RunEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+//// PLAYER
+fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, class: Res<DogClass>) {
+    // The course has a dog of its own that runs instead of flying
+    if *mode != GameMode::Balloon {
+        return;
+    }
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(class.balloon_size()),
+                ..default()
+            },
+            texture: asset_server.load("sprites/dog-with-balloons-white.png"),
+            transform: Transform::from_xyz(-400., 0., 10.),
+            ..default()
+        })
+        //.insert(BackAndForth)
+        .insert(Player)
+        .insert(RunEntity);
+}
+
+// System currently not in use!!!
+fn up_and_down_movement(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut positions: Query<&mut Transform, With<UpAndDown>>,
+) {
+    for mut transform in positions.iter_mut() {
+        if keyboard_input.pressed(KeyCode::Up) {
+            transform.translation.y += 2.;
+        }
+        if keyboard_input.pressed(KeyCode::Down) {
+            transform.translation.y -= 2.;
+        }
+    }
+}
+
+// System currently not in use!!!
+fn back_and_forth_movement(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut positions: Query<&mut Transform, With<BackAndForth>>,
+) {
+    for mut transform in positions.iter_mut() {
+        /*
+        if keyboard_input.pressed(KeyCode::Left) {
+            transform.translation.x -= 3.;
+            continue;
+        }
+        */
+        if keyboard_input.pressed(KeyCode::Right) {
+            transform.translation.x += 3.;
+            continue;
+        }   
+        transform.translation.x -= 1.5;
+    }
+}
+
+fn read_keyboard_input(keyboard_input: Res<Input<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    *player_input = PlayerInput {
+        flap: keyboard_input.pressed(KeyCode::Space),
+    };
+}
+
+fn player_movement(
+    player_input: Res<PlayerInput>,
+    class: Res<DogClass>,
+    mut positions: Query<&mut Transform, With<Player>>,
+) {
+    for mut transform in positions.iter_mut() {
+        if player_input.flap {
+            // Go up but not too high
+            transform.translation.y = (transform.translation.y + class.lift()).min(class.flight_top());
+            continue;
+        }
+        // Go down but not below ground
+        transform.translation.y = (transform.translation.y - 2.).max(class.ground_y());
+    }
+}
+
+// Where a hawk flying at `speed` starts, HAWK_LEAD_TICKS away from the screen
+fn hawk_spawn_x(speed: f32) -> f32 {
+    WINDOW_WIDTH / 2.0 + HAWK_SIZE / 2.0 + HAWK_LEAD_TICKS * speed
+}
+
+/// Hawk
+fn spawn_hawk<'w, 's, 'a>(commands: &'a mut Commands<'w, 's>, asset_server: &AssetServer, position: Vec2, speed: f32, ai: HawkAi) -> EntityCommands<'w, 's, 'a> {
+    let mut hawk = commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            custom_size: Some(Vec2::splat(HAWK_SIZE)),
+            ..default()
+        },
+        texture: asset_server.load("sprites/hawk.png"),
+        transform: Transform::from_xyz(position.x, position.y, 1.),
+        ..default()
+    });
+    hawk.insert(Hawk(speed))
+        .insert(ai)
+        .insert(HawkPass::default())
+        .insert(RunEntity);
+    hawk
+}
+
+fn hawk_spawner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<HawkConfig>,
+    wave_config: Res<WaveConfig>,
+    mut waves: ResMut<WaveDirector>,
+    mut rng: ResMut<GameRng>,
+) {
+    let randomizer: f32 = rng.0.gen_range(-0.0..1000.0);
+    if randomizer > 999.0 {
+        let y = rng.0.gen_range(-200.0..400.0);
+        let speed = rng.0.gen_range(2.5..4.5);
+        let position = Vec2::new(hawk_spawn_x(speed), y);
+        let ai = config.roll(&mut rng.0);
+        spawn_hawk(&mut commands, &asset_server, position, speed, ai);
+    }
+    if let Some(wave) = waves.tick(&wave_config, &mut rng.0) {
+        for offset in waves::formation(&wave.pattern, &mut rng.0) {
+            let position = Vec2::new(hawk_spawn_x(wave.speed) + offset.x, offset.y);
+            spawn_hawk(&mut commands, &asset_server, position, wave.speed, config.ai(wave.behaviour)).insert(WaveHawk);
+        }
+    }
+}
+
+//// BONE
+fn bone_spawner(mut commands: Commands, library: Res<BoneLibrary>, mode: Res<GameMode>, mut rng: ResMut<GameRng>) {
+    if *mode != GameMode::Balloon {
+        return;
+    }
+    for _ in 1..10 {
+        let translation = Vec3::new(rng.0.gen_range(-500.0..500.0), rng.0.gen_range(-350.0..350.0), 0.0);
+        let rotation = Quat::from_rotation_z(rng.0.gen_range(0.0..3.14),);
+        let def = library.roll(&mut rng.0);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(BONE_WIDTH, BONE_HEIGHT)),
+                    ..default()
+                },
+                texture: library.texture(def),
+                //transform: Transform::from_xyz(thread_rng().gen_range(-500.0..500.0), thread_rng().gen_range(-350.0..350.0), 0.),
+                //transform: Transform { translation: Vec3::new(600.0, 15.0, 0.0), rotation: Quat::from_rotation_z(1.5,), scale: Vec3::new(1.0, 1.0, 1.0) },
+                transform: Transform { 
+                    translation,
+                    rotation,
+                    scale: Vec3::new(1.0, 1.0, 1.0)
+                },
+                ..default()
+            })
+            .insert(Bone {
+                kind: library.def(def).kind,
+                def,
+                base_y: translation.y,
+            })
+            .insert(RunEntity);
+    }
+}
+
+// Turns a bone that was picked up or left the screen into a new one of a random kind
+fn recycle_bone(bone: &mut Bone, texture: &mut Handle<Image>, y: f32, library: &BoneLibrary, rng: &mut StdRng) {
+    let def = library.roll(rng);
+    *bone = Bone {
+        kind: library.def(def).kind,
+        def,
+        base_y: y,
+    };
+    *texture = library.texture(def);
+}
+
+fn bone_mover(
+    library: Res<BoneLibrary>,
+    mut rng: ResMut<GameRng>,
+    mut positions: Query<(&mut Bone, &mut Transform, &mut Handle<Image>)>,
+) {
+    for (mut bone, mut transform, mut texture) in positions.iter_mut() {
+        let def = library.def(bone.def);
+        // X-AXIS
+        transform.translation.x -= WORLD_SPEED;
+        // Y-AXIS, the wave follows the x position so it needs no state of its own
+        if def.wave_height != 0.0 {
+            let phase = transform.translation.x / def.wave_length * std::f32::consts::TAU;
+            transform.translation.y = bone.base_y + def.wave_height * phase.sin();
+        }
+
+        // ROTATION
+        let rotation_delta = Quat::from_rotation_z(6.28 * def.spin/60.0);
+        transform.rotation *= rotation_delta;
+        //let (_, angle) = transform.rotation.to_axis_angle();
+        //println!("angle: {} ", angle);
+        //transform.rotate(Quat::from_rotation_z(angle + 0.1));
+        // Rewind back to right incase object moves out of sight
+        if transform.translation.x < -650.0 {
+            transform.translation.x += 1280.0;
+            transform.translation.y = rng.0.gen_range(-350.0..350.0);
+            recycle_bone(&mut bone, &mut texture, transform.translation.y, &library, &mut rng.0);
+        }
+    }
+}
+
+fn hawk_mover(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    class: Res<DogClass>,
+    mut positions: Query<(Entity, &mut Transform, &Hawk, &mut HawkAi, Option<&WaveHawk>), Without<Player>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    // Hawks hit the dog's balloons, that's what they aim for
+    let dog = match player_query.get_single() {
+        Ok(player_tf) => Vec2::new(player_tf.translation.x, player_tf.translation.y + class.balloon_size().y / 4.0),
+        Err(_) => return,
+    };
+    for (hawk, mut transform, speed, mut ai, wave) in positions.iter_mut() {
+        ai.step(speed.0, &mut transform.translation, dog);
+        if transform.translation.x < -800.0 && wave.is_some() {
+            commands.entity(hawk).despawn_recursive();
+        } else if transform.translation.x < -800.0 {
+            transform.translation.x = hawk_spawn_x(speed.0);
+            transform.translation.y = rng.0.gen_range(-300.0..350.0);
+            ai.reset();
+        }
+    }
+}
+
+// Collision management
+fn player_collide_with_bone(
+    mut total_points: ResMut<TotalPoints>,
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut rng: ResMut<GameRng>,
+    mut bone_pickups: EventWriter<BonePickupEvent>,
+    library: Res<BoneLibrary>,
+    class: Res<DogClass>,
+    mut bone_query: Query<(&mut Bone, &mut Transform, &mut Handle<Image>), Without<Player>>,
+    player_query: Query<(&Player, &Transform), Without<Bone>>) { 
+    // The dog is the lower half of the sprite, the balloons the upper one
+    let size = class.balloon_size();
+	// iterate through the Bones
+	for (mut bone, mut bone_tf, mut texture) in bone_query.iter_mut() {
+        // Check if the bone collides
+        for (player, player_tf) in player_query.iter() {
+            //println!("Player position: {}", player_tf.translation.y);     
+            if collide(bone_tf.translation, Vec2::new(BONE_WIDTH, BONE_HEIGHT),Vec3::new(player_tf.translation.x, player_tf.translation.y-size.y/4.0, player_tf.translation.z), Vec2::new(size.x, size.y/2.0)).is_some() {
+                //println!("Collision happened at: {} {}", bone_tf.translation.x, bone_tf.translation.y);
+                //player.total_points += 1;
+                //player1.set_total_points(2);
+                //println!("Bones: {}", total_points.0);
+                // A decoy isn't a bone at all and costs the combo
+                let points = if bone.kind == BoneKind::Decoy {
+                    *combo = Combo::default();
+                    0
+                } else {
+                    total_points.0 += 1;
+                    combo.pick_up(library.def(bone.def).points)
+                };
+                score.0 += points;
+                bone_pickups.send(BonePickupEvent {
+                    position: bone_tf.translation,
+                    kind: bone.kind,
+                    points,
+                });
+                bone_tf.translation.x += 1000.0;
+                bone_tf.translation.y = rng.0.gen_range(-380.0..380.0);
+                recycle_bone(&mut bone, &mut texture, bone_tf.translation.y, &library, &mut rng.0);
+            }
+        }
+    }
+}
+
+// Collision management
+fn player_collide_with_hawk(
+    mut commands: Commands,
+    class: Res<DogClass>,
+    mut hawk_hits: EventWriter<HawkHitEvent>,
+    mut hawk_query: Query<(&Transform, &mut HawkPass), (With<Hawk>, Without<Player>)>,
+    mut player_query: Query<(Entity, &mut Transform), (With<Player>, Without<Hawk>, Without<Invulnerable>)>) { 
+    let size = class.balloon_size();
+    for (player, mut player_tf) in player_query.iter_mut() {
+        // One hit at a time, the dog can't be hit again while invulnerable
+        let hawk = hawk_query.iter_mut().find(|(hawk_tf, _)| {
+            collide(hawk_tf.translation, Vec2::new(60.0, 60.0),Vec3::new(player_tf.translation.x, player_tf.translation.y+size.y/4.0, player_tf.translation.z), Vec2::new(size.x, size.y/2.0)).is_some()
+        });
+        if let Some((hawk_tf, mut pass)) = hawk {
+            pass.hit_dog = true;
+            hawk_hits.send(HawkHitEvent {
+                hawk: hawk_tf.translation,
+                player: player_tf.translation,
+            });
+            player_tf.translation.y = class.ground_y();
+            commands.entity(player).insert(Invulnerable(INVULNERABLE_TICKS));
+        }
+    }
+}
+
+// A hawk passing the dog's x without having hit it on the way counts as dodged
+fn count_dodges(
+    mut hawk_dodges: EventWriter<HawkDodgedEvent>,
+    player_query: Query<&Transform, With<Player>>,
+    mut hawk_query: Query<(&Transform, &mut HawkPass), With<Hawk>>,
+) {
+    let player_x = match player_query.get_single() {
+        Ok(player_tf) => player_tf.translation.x,
+        Err(_) => return,
+    };
+    for (hawk_tf, mut pass) in hawk_query.iter_mut() {
+        let x = hawk_tf.translation.x;
+        let passed = pass.prev_x.map_or(false, |prev_x| prev_x > player_x && x <= player_x);
+        pass.prev_x = Some(x);
+        if x > WINDOW_WIDTH / 2.0 {
+            // Back off screen on the right for another pass
+            pass.hit_dog = false;
+        } else if passed && !pass.hit_dog {
+            hawk_dodges.send(HawkDodgedEvent);
+        }
+    }
+}
+
+fn lose_lives(
+    mut lives: ResMut<Lives>,
+    mut state: ResMut<State<AppState>>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+) {
+    if hawk_hits.iter().count() == 0 {
+        return;
+    }
+    lives.0 = lives.0.saturating_sub(1);
+    if lives.0 == 0 {
+        let _ = state.set(AppState::GameOver);
+    }
+}
+
+fn update_combo(mut combo: ResMut<Combo>, mut hawk_hits: EventReader<HawkHitEvent>) {
+    if hawk_hits.iter().count() > 0 {
+        *combo = Combo::default();
+    } else if combo.ticks_left > 0 {
+        combo.ticks_left -= 1;
+        if combo.ticks_left == 0 {
+            *combo = Combo::default();
+        }
+    }
+}
+
+fn wear_off_invulnerability(mut commands: Commands, mut query: Query<(Entity, &mut Invulnerable)>) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        invulnerable.0 = invulnerable.0.saturating_sub(1);
+        if invulnerable.0 == 0 {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
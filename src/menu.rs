@@ -0,0 +1,401 @@
+//! Keyboard driven menus for the main menu, pause, settings and game over states,
+//! and for setting up a competition and its standings.
+//!
+//! Up/Down pick an entry, Enter selects it and Left/Right change a setting. Escape
+//! pauses a run and backs out of menus.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::achievements::Achievements;
+use crate::audio::PlaySound;
+use crate::competition::{Competition, CompetitionSetup, SetupOption};
+use crate::course::{CourseRun, SelectedCourse};
+use crate::settings::{Setting, Settings};
+use crate::stats::LifetimeStats;
+use crate::{AppState, GameMode, Score, TotalPoints};
+
+const MENU_STATES: [AppState; 8] = [
+    AppState::MainMenu,
+    AppState::Paused,
+    AppState::Settings,
+    AppState::GameOver,
+    AppState::Achievements,
+    AppState::Stats,
+    AppState::CompetitionSetup,
+    AppState::Standings,
+];
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+const ITEM_COLOR: Color = Color::WHITE;
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuAction {
+    Play,
+    PlayCourse,
+    OpenDesigner,
+    OpenCompetition,
+    // Another run of the same mode
+    PlayAgain,
+    // The next competitor's run
+    NextRun,
+    Resume,
+    OpenSettings,
+    OpenAchievements,
+    OpenStats,
+    Change(Setting),
+    Setup(SetupOption),
+    StartCompetition,
+    OpenStandings,
+    ExportStandings,
+    EndRun,
+    MainMenu,
+    Back,
+    Quit,
+}
+
+impl MenuAction {
+    fn label(self, settings: &Settings, setup: &CompetitionSetup) -> String {
+        let label = match self {
+            MenuAction::Play => "Play",
+            MenuAction::PlayCourse => "Agility course",
+            MenuAction::OpenDesigner => "Course designer",
+            MenuAction::OpenCompetition => "Competition",
+            MenuAction::PlayAgain => "Play again",
+            MenuAction::NextRun => "Next run",
+            MenuAction::Resume => "Resume",
+            MenuAction::OpenSettings => "Settings",
+            MenuAction::OpenAchievements => "Achievements",
+            MenuAction::OpenStats => "Statistics",
+            MenuAction::Change(setting) => return setting.label(settings),
+            MenuAction::Setup(option) => return option.label(setup),
+            MenuAction::StartCompetition => "Start",
+            MenuAction::OpenStandings => "Standings",
+            MenuAction::ExportStandings => "Export standings",
+            MenuAction::EndRun => "End run",
+            MenuAction::MainMenu => "Main menu",
+            MenuAction::Back => "Back",
+            MenuAction::Quit => "Quit",
+        };
+        label.to_string()
+    }
+}
+
+// Title and entries of the menu shown in a state
+fn menu_for(state: AppState, competition: &Competition) -> (&'static str, Vec<MenuAction>) {
+    match state {
+        AppState::MainMenu => (
+            "Agility Camp",
+            vec![
+                MenuAction::Play,
+                MenuAction::PlayCourse,
+                MenuAction::OpenDesigner,
+                MenuAction::OpenCompetition,
+                MenuAction::OpenAchievements,
+                MenuAction::OpenStats,
+                MenuAction::OpenSettings,
+                MenuAction::Quit,
+            ],
+        ),
+        AppState::Paused => (
+            "Paused",
+            vec![
+                MenuAction::Resume,
+                MenuAction::OpenSettings,
+                MenuAction::EndRun,
+                MenuAction::MainMenu,
+            ],
+        ),
+        AppState::Settings => (
+            "Settings",
+            Setting::ALL
+                .iter()
+                .map(|setting| MenuAction::Change(*setting))
+                .chain([MenuAction::Back])
+                .collect(),
+        ),
+        AppState::GameOver if competition.finished() => (
+            "Game over",
+            vec![MenuAction::OpenStandings, MenuAction::MainMenu],
+        ),
+        AppState::GameOver if competition.active() => {
+            ("Game over", vec![MenuAction::NextRun, MenuAction::MainMenu])
+        }
+        AppState::GameOver => (
+            "Game over",
+            vec![MenuAction::PlayAgain, MenuAction::MainMenu],
+        ),
+        AppState::CompetitionSetup => (
+            "Competition",
+            SetupOption::ALL
+                .iter()
+                .map(|option| MenuAction::Setup(*option))
+                .chain([MenuAction::StartCompetition, MenuAction::Back])
+                .collect(),
+        ),
+        AppState::Standings => (
+            "Standings",
+            vec![MenuAction::ExportStandings, MenuAction::MainMenu],
+        ),
+        AppState::Achievements => ("Achievements", vec![MenuAction::Back]),
+        AppState::Stats => ("Statistics", vec![MenuAction::Back]),
+        AppState::InGame | AppState::Designer => ("", vec![]),
+    }
+}
+
+// Start: --- Components
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuItem {
+    index: usize,
+    action: MenuAction,
+}
+// End: --- Components
+
+// Start: --- Resources
+#[derive(Default)]
+struct MenuCursor(usize);
+// End: --- Resources
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuCursor>()
+            .add_system(menu_input)
+            .add_system(update_menu_items.after(menu_input));
+        // Menus covered by another one come back when it closes
+        for state in MENU_STATES {
+            app.add_system_set(SystemSet::on_enter(state).with_system(spawn_menu))
+                .add_system_set(SystemSet::on_resume(state).with_system(spawn_menu))
+                .add_system_set(SystemSet::on_exit(state).with_system(despawn_menu))
+                .add_system_set(SystemSet::on_pause(state).with_system(despawn_menu));
+        }
+    }
+}
+
+fn menu_text(value: String, font: Handle<Font>, font_size: f32) -> TextBundle {
+    TextBundle {
+        style: Style {
+            margin: Rect::all(Val::Px(8.0)),
+            ..default()
+        },
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font,
+                font_size,
+                color: ITEM_COLOR,
+            },
+            default(),
+        ),
+        ..default()
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<AppState>>,
+    settings: Res<Settings>,
+    achievements: Res<Achievements>,
+    stats: Res<LifetimeStats>,
+    course: Option<Res<CourseRun>>,
+    competition: Res<Competition>,
+    setup: Res<CompetitionSetup>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    mut cursor: ResMut<MenuCursor>,
+) {
+    cursor.0 = 0;
+    let (title, items) = menu_for(*state.current(), &competition);
+    let mut title = title.to_string();
+    // Text between the title and the entries
+    let mut lines = match (state.current(), course) {
+        (AppState::GameOver, Some(course)) => {
+            title = "Judge's sheet".to_string();
+            course.sheet()
+        }
+        (AppState::GameOver, None) => {
+            vec![format!("Score: {}  Bones: {}", score.0, total_points.0)]
+        }
+        (AppState::Achievements, _) => achievements.lines(),
+        (AppState::Stats, _) => stats.lines(),
+        (AppState::Standings, _) => competition.table(),
+        _ => vec![],
+    };
+    if *state.current() == AppState::GameOver {
+        if let Some((name, next)) = competition.after_run() {
+            title = format!("{}: {}", name, title);
+            lines.push(next);
+        }
+    }
+    let line_size = if lines.len() > 1 { 24.0 } else { 36.0 };
+    let font: Handle<Font> = asset_server.load(FONT);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                // Top to bottom
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: UiColor(BACKDROP_COLOR),
+            ..default()
+        })
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent.spawn_bundle(menu_text(title, font.clone(), 64.0));
+            for line in lines {
+                parent.spawn_bundle(menu_text(line, font.clone(), line_size));
+            }
+            for (index, action) in items.into_iter().enumerate() {
+                parent
+                    .spawn_bundle(menu_text(
+                        action.label(&settings, &setup),
+                        font.clone(),
+                        32.0,
+                    ))
+                    .insert(MenuItem { index, action });
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, query: Query<Entity, With<MenuRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<State<AppState>>,
+    mut cursor: ResMut<MenuCursor>,
+    mut settings: ResMut<Settings>,
+    mut mode: ResMut<GameMode>,
+    mut setup: ResMut<CompetitionSetup>,
+    mut competition: ResMut<Competition>,
+    selected: Res<SelectedCourse>,
+    mut sounds: EventWriter<PlaySound>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let current = *state.current();
+    if current == AppState::InGame {
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            let _ = state.push(AppState::Paused);
+        }
+        return;
+    }
+    let (_, items) = menu_for(current, &competition);
+    if items.is_empty() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        sounds.send(PlaySound("menu_select"));
+        let _ = match current {
+            AppState::MainMenu => {
+                exit.send(AppExit);
+                Ok(())
+            }
+            AppState::GameOver | AppState::Standings => state.replace(AppState::MainMenu),
+            _ => state.pop(),
+        };
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        cursor.0 = (cursor.0 + items.len() - 1) % items.len();
+        sounds.send(PlaySound("menu_move"));
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        cursor.0 = (cursor.0 + 1) % items.len();
+        sounds.send(PlaySound("menu_move"));
+    }
+
+    let action = items[cursor.0.min(items.len() - 1)];
+    for (key, up) in [(KeyCode::Left, false), (KeyCode::Right, true)] {
+        if !keyboard_input.just_pressed(key) {
+            continue;
+        }
+        match action {
+            MenuAction::Change(setting) => setting.change(&mut settings, up),
+            MenuAction::Setup(option) => option.change(&mut setup, up),
+            _ => continue,
+        }
+        sounds.send(PlaySound("menu_move"));
+    }
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    sounds.send(PlaySound("menu_select"));
+    let _ = match action {
+        MenuAction::Play => {
+            *mode = GameMode::Balloon;
+            state.set(AppState::InGame)
+        }
+        MenuAction::PlayCourse => {
+            *mode = GameMode::Course;
+            state.set(AppState::InGame)
+        }
+        MenuAction::OpenDesigner => state.set(AppState::Designer),
+        MenuAction::OpenCompetition => state.push(AppState::CompetitionSetup),
+        MenuAction::Setup(option) => {
+            option.change(&mut setup, true);
+            Ok(())
+        }
+        MenuAction::StartCompetition => {
+            *competition = Competition::new(&setup, &selected, settings.dog_class);
+            *mode = setup.mode;
+            state.replace(AppState::InGame)
+        }
+        MenuAction::NextRun => state.set(AppState::InGame),
+        MenuAction::OpenStandings => state.set(AppState::Standings),
+        MenuAction::ExportStandings => {
+            competition.export();
+            state.restart()
+        }
+        MenuAction::PlayAgain => state.set(AppState::InGame),
+        MenuAction::Resume | MenuAction::Back => state.pop(),
+        MenuAction::OpenSettings => state.push(AppState::Settings),
+        MenuAction::OpenAchievements => state.push(AppState::Achievements),
+        MenuAction::OpenStats => state.push(AppState::Stats),
+        MenuAction::Change(setting) => {
+            setting.change(&mut settings, true);
+            Ok(())
+        }
+        MenuAction::EndRun => state.replace(AppState::GameOver),
+        MenuAction::MainMenu => state.replace(AppState::MainMenu),
+        MenuAction::Quit => {
+            exit.send(AppExit);
+            Ok(())
+        }
+    };
+}
+
+fn update_menu_items(
+    cursor: Res<MenuCursor>,
+    settings: Res<Settings>,
+    setup: Res<CompetitionSetup>,
+    mut query: Query<(&MenuItem, &mut Text)>,
+) {
+    for (item, mut text) in query.iter_mut() {
+        let label = item.action.label(&settings, &setup);
+        let color = if item.index == cursor.0 {
+            SELECTED_COLOR
+        } else {
+            ITEM_COLOR
+        };
+        let section = &text.sections[0];
+        if section.value != label || section.style.color != color {
+            let section = &mut text.sections[0];
+            section.value = label;
+            section.style.color = color;
+        }
+    }
+}
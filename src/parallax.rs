@@ -0,0 +1,99 @@
+//! Layered parallax background defined in `assets/data/parallax.ron`.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{data, SimulationRunning, WINDOW_WIDTH};
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct ParallaxLayer {
+    name: String,
+    texture: String,
+    size: (f32, f32),
+    y: f32,
+    z: f32,
+    // Pixels per tick towards the left
+    speed: f32,
+    // Distance between two copies of the texture
+    spacing: f32,
+}
+
+#[derive(Deserialize)]
+struct ParallaxConfig {
+    layers: Vec<ParallaxLayer>,
+}
+
+impl Default for ParallaxConfig {
+    fn default() -> Self {
+        ParallaxConfig {
+            layers: vec![ParallaxLayer {
+                name: "hills".to_string(),
+                texture: "sprites/hills.png".to_string(),
+                size: (1280.0, 300.0),
+                y: -250.0,
+                z: -10.0,
+                speed: 0.3,
+                spacing: 1280.0,
+            }],
+        }
+    }
+}
+// End: --- Data
+
+// Start: --- Components
+#[derive(Component)]
+struct ParallaxTile {
+    speed: f32,
+    // Total width covered by all copies of the layer
+    span: f32,
+    // Tiles further left than this are moved back by `span`
+    wrap_at: f32,
+}
+// End: --- Components
+
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_parallax_layers)
+            .add_system(parallax_scroll.with_run_criteria(SimulationRunning));
+    }
+}
+
+fn spawn_parallax_layers(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let config: ParallaxConfig = data::load("parallax.ron");
+    for layer in config.layers.iter() {
+        let (width, height) = layer.size;
+        let spacing = layer.spacing.max(width);
+        // Enough copies that one can wrap around while the others cover the screen
+        let copies = ((WINDOW_WIDTH + width) / spacing).ceil().max(1.0) as usize;
+        debug!("Parallax layer {} with {} tiles", layer.name, copies);
+        for i in 0..copies {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(width, height)),
+                        ..default()
+                    },
+                    texture: asset_server.load(layer.texture.as_str()),
+                    transform: Transform::from_xyz(i as f32 * spacing, layer.y, layer.z),
+                    ..default()
+                })
+                .insert(ParallaxTile {
+                    speed: layer.speed,
+                    span: copies as f32 * spacing,
+                    wrap_at: -(WINDOW_WIDTH + width) / 2.0,
+                });
+        }
+    }
+}
+
+fn parallax_scroll(mut tiles: Query<(&mut Transform, &ParallaxTile)>) {
+    for (mut transform, tile) in tiles.iter_mut() {
+        transform.translation.x -= tile.speed;
+        if transform.translation.x < tile.wrap_at {
+            transform.translation.x += tile.span;
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Lightweight CPU particles for pickups and hits, defined in `assets/data/particles.ron`.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::bones::BoneKind;
+use crate::{data, BonePickupEvent, HawkHitEvent, ScenerySeed};
+
+const PARTICLES_FILE: &str = "particles.ron";
+const PARTICLE_RNG_STREAM: u64 = 3;
+const PARTICLE_Z: f32 = 30.0;
+
+// Start: --- Data
+#[derive(Deserialize)]
+struct EffectDef {
+    count: usize,
+    speed: (f32, f32),
+    // Degrees, 90 is straight up
+    direction: f32,
+    spread: f32,
+    lifetime: (f32, f32),
+    size: (f32, f32),
+    colors: Vec<(f32, f32, f32, f32)>,
+    gravity: f32,
+    fade: bool,
+    // Radians per second, random direction
+    #[serde(default)]
+    spin: f32,
+    #[serde(default)]
+    offset: (f32, f32),
+    #[serde(default)]
+    texture: Option<String>,
+}
+
+impl EffectDef {
+    // The first (min, max) range that is the wrong way round, bursts pick from them
+    fn inverted_range(&self) -> Option<&'static str> {
+        [
+            ("speed", self.speed),
+            ("lifetime", self.lifetime),
+            ("size", self.size),
+        ]
+        .into_iter()
+        .find(|(_, (min, max))| min > max)
+        .map(|(name, _)| name)
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct ParticleConfig {
+    effects: HashMap<String, EffectDef>,
+}
+
+impl ParticleConfig {
+    // Effects with a range the wrong way round are left out, as if they weren't there
+    fn load() -> Self {
+        let mut config: ParticleConfig = data::load(PARTICLES_FILE);
+        config
+            .effects
+            .retain(|name, effect| match effect.inverted_range() {
+                Some(range) => {
+                    warn!(
+                        "Effect {} in {} has its {} the wrong way round",
+                        name, PARTICLES_FILE, range
+                    );
+                    false
+                }
+                None => true,
+            });
+        config
+    }
+}
+// End: --- Data
+
+// Start: --- Components
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    gravity: f32,
+    spin: f32,
+    age: f32,
+    lifetime: f32,
+    fade: bool,
+    alpha: f32,
+}
+// End: --- Components
+
+// Start: --- Resources
+struct ParticleEffects {
+    effects: HashMap<String, EffectDef>,
+    textures: HashMap<String, Handle<Image>>,
+    rng: StdRng,
+}
+
+impl ParticleEffects {
+    // Spawns one burst of the named effect
+    fn burst(&mut self, commands: &mut Commands, name: &str, position: Vec3) {
+        let effect = match self.effects.get(name) {
+            Some(effect) => effect,
+            None => return,
+        };
+        let rng = &mut self.rng;
+        let texture = effect
+            .texture
+            .as_ref()
+            .and_then(|texture| self.textures.get(texture))
+            .cloned()
+            .unwrap_or_default();
+        let origin = position.truncate() + Vec2::new(effect.offset.0, effect.offset.1);
+        for _ in 0..effect.count {
+            let angle = (effect.direction + effect.spread * (rng.gen::<f32>() - 0.5)).to_radians();
+            let speed = rng.gen_range(effect.speed.0..=effect.speed.1);
+            let size = rng.gen_range(effect.size.0..=effect.size.1);
+            let (r, g, b, a) = effect
+                .colors
+                .choose(rng)
+                .copied()
+                .unwrap_or((1.0, 1.0, 1.0, 1.0));
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(r, g, b, a),
+                        custom_size: Some(Vec2::new(size, size)),
+                        ..default()
+                    },
+                    texture: texture.clone(),
+                    transform: Transform::from_translation(origin.extend(PARTICLE_Z)),
+                    ..default()
+                })
+                .insert(Particle {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                    gravity: effect.gravity,
+                    spin: effect.spin * if rng.gen() { 1.0 } else { -1.0 },
+                    age: 0.0,
+                    lifetime: rng.gen_range(effect.lifetime.0..=effect.lifetime.1),
+                    fade: effect.fade,
+                    alpha: a,
+                });
+        }
+    }
+}
+// End: --- Resources
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_particle_effects)
+            .add_system(spawn_pickup_effects)
+            .add_system(update_particles);
+    }
+}
+
+fn load_particle_effects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scenery_seed: Res<ScenerySeed>,
+) {
+    let config = ParticleConfig::load();
+    let textures = config
+        .effects
+        .values()
+        .filter_map(|effect| effect.texture.clone())
+        .map(|texture| (texture.clone(), asset_server.load(texture.as_str())))
+        .collect();
+    commands.insert_resource(ParticleEffects {
+        effects: config.effects,
+        textures,
+        rng: scenery_seed.rng(PARTICLE_RNG_STREAM),
+    });
+}
+
+fn spawn_pickup_effects(
+    mut commands: Commands,
+    effects: Option<ResMut<ParticleEffects>>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+) {
+    let mut effects = match effects {
+        Some(effects) => effects,
+        None => return,
+    };
+    for pickup in bone_pickups.iter() {
+        let name = match pickup.kind {
+            BoneKind::Plain => "bone_sparkle",
+            BoneKind::Golden => "golden_sparkle",
+            BoneKind::Spinning => "spinning_sparkle",
+            BoneKind::Decoy => "decoy_dust",
+        };
+        effects.burst(&mut commands, name, pickup.position);
+    }
+    for hit in hawk_hits.iter() {
+        effects.burst(&mut commands, "feather_puff", hit.hawk);
+        effects.burst(&mut commands, "balloon_pop", hit.player);
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        particle.velocity.y += particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.0);
+        transform.rotate(Quat::from_rotation_z(particle.spin * delta));
+        if particle.fade {
+            let left = 1.0 - particle.age / particle.lifetime;
+            sprite.color.set_a(particle.alpha * left);
+        }
+    }
+}
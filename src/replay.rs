@@ -0,0 +1,468 @@
+//! Input recording and deterministic replay.
+//!
+//! Run with `--record <file>` to save the per-tick input stream of a run, or with
+//! `--replay <file>` to play a saved run back. `--seed <n>` fixes the RNG seed of a
+//! live run. A replay stores the RNG seed, the dog's size class, a hash of the
+//! gameplay constants and the run-length encoded inputs, plus the final score so
+//! playback can be checked.
+//! `--ghost` saves the run under `replays/` and races it against the best earlier
+//! run with the same seed. Recorded runs also keep the Player, Hawk and Bone
+//! positions of every tick for the replay viewer (`--view <file>`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::competition::{AiDog, Competition};
+use crate::dog::DogClass;
+use crate::hawks::HawkConfig;
+use crate::settings::Settings;
+use crate::waves::WaveConfig;
+use crate::{
+    bones, AppState, Bone, BonePickupEvent, GameMode, GameRng, GameStep, Hawk, HawkHitEvent,
+    Player, PlayerInput, ScenerySeed, Score, SimulationRunning, TotalPoints, BONES_PER_MULTIPLIER,
+    BONE_HEIGHT, BONE_WIDTH, COMBO_TICKS, INVULNERABLE_TICKS, MAX_MULTIPLIER, PLAYER_HEIGHT,
+    PLAYER_WIDTH, STARTING_LIVES, WINDOW_HEIGHT, WINDOW_WIDTH, WORLD_SPEED,
+};
+
+const REPLAY_VERSION: u32 = 1;
+pub const REPLAY_DIR: &str = "replays";
+// Every frame simulates one tick, the game is tuned for 60 frames per second
+pub const TICKS_PER_SECOND: f64 = 60.0;
+
+// Start: --- Replay file
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    // Replays from before there were classes are of medium dogs
+    #[serde(default)]
+    pub dog: DogClass,
+    pub config_hash: u64,
+    // (number of ticks, input bits) pairs
+    pub inputs: Vec<(u32, u8)>,
+    pub points: u32,
+    #[serde(default)]
+    pub score: u32,
+    pub hawk_hits: u32,
+    // Player height on every tick, rounded to whole pixels
+    #[serde(default)]
+    pub track: Vec<i16>,
+    // Ticks on which a bone was picked up
+    #[serde(default)]
+    pub bone_ticks: Vec<u32>,
+    // Ticks on which the dog was hit by a hawk
+    #[serde(default)]
+    pub hit_ticks: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<Frame>,
+}
+
+// Snapshot of the world at the end of a tick, positions rounded to whole pixels
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Frame {
+    pub player: (i16, i16),
+    pub hawks: Vec<(i16, i16)>,
+    // x, y and rotation in degrees
+    pub bones: Vec<(i16, i16, i16)>,
+    pub points: u32,
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            config_hash: config_hash(),
+            ..default()
+        }
+    }
+
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        ron::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), String> {
+        let text = ron::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub(crate) fn push_input(&mut self, input: PlayerInput) {
+        let bits = input.to_bits();
+        match self.inputs.last_mut() {
+            Some((count, last)) if *last == bits => *count += 1,
+            _ => self.inputs.push((1, bits)),
+        }
+    }
+
+    // Bones collected up to and including the given tick
+    pub fn points_at(&self, tick: u32) -> u32 {
+        self.bone_ticks.partition_point(|t| *t <= tick) as u32
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.inputs.iter().map(|(count, _)| count).sum()
+    }
+
+    // Input for the given tick, None once the recording has run out
+    pub(crate) fn input_at(&self, tick: u32) -> Option<PlayerInput> {
+        let mut start = 0;
+        for (count, bits) in self.inputs.iter() {
+            if tick < start + count {
+                return Some(PlayerInput::from_bits(*bits));
+            }
+            start += count;
+        }
+        None
+    }
+}
+
+// Hash of every constant and data file that changes the outcome of a run. A replay
+// recorded with different values will not play back the same way.
+pub fn config_hash() -> u64 {
+    let constants = format!(
+        "{:?}",
+        (
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT,
+            PLAYER_WIDTH,
+            PLAYER_HEIGHT,
+            BONE_WIDTH,
+            BONE_HEIGHT,
+            WORLD_SPEED,
+            INVULNERABLE_TICKS,
+            STARTING_LIVES,
+            (COMBO_TICKS, BONES_PER_MULTIPLIER, MAX_MULTIPLIER),
+            DogClass::ALL.map(|class| (class.scale(), class.lift())),
+            (
+                bones::load_config().kinds,
+                HawkConfig::load(),
+                WaveConfig::load()
+            )
+        )
+    );
+    // FNV-1a, stable between builds unlike the std hasher
+    constants.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+// End: --- Replay file
+
+// Start: --- Resources
+pub enum RunMode {
+    Live { record: Option<PathBuf>, ghost: bool },
+    Playback,
+}
+
+pub struct ReplayState {
+    // Counted from 1 since the game started
+    pub run: u32,
+    pub mode: RunMode,
+    pub replay: Replay,
+    pub tick: u32,
+    pub hawk_hits: u32,
+}
+// End: --- Resources
+
+// Command line options of a run
+#[derive(Clone, Default)]
+pub struct RunArgs {
+    pub seed: Option<u64>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub view: Option<PathBuf>,
+    pub ghost: bool,
+}
+
+impl RunArgs {
+    // Parses `--seed`, `--record`, `--replay`, `--view` and `--ghost`
+    pub fn from_env() -> Self {
+        let mut run_args = RunArgs::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => run_args.seed = args.next().and_then(|s| s.parse::<u64>().ok()),
+                "--record" => run_args.record = args.next().map(PathBuf::from),
+                "--replay" => run_args.replay = args.next().map(PathBuf::from),
+                "--view" => run_args.view = args.next().map(PathBuf::from),
+                "--ghost" => run_args.ghost = true,
+                _ => warn!("Unknown argument: {}", arg),
+            }
+        }
+        run_args
+    }
+}
+
+// `--record` keeps the first run under the name it was given and numbers the later
+// ones, `run.replay`, `run-2.replay` and so on
+fn numbered(path: &Path, run: u32) -> PathBuf {
+    if run <= 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, run, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, run),
+    };
+    path.with_file_name(name)
+}
+
+// A competition's seed takes the place of the one from the command line
+fn start_run(args: &RunArgs, seed: Option<u64>, run: u32) -> (RunMode, Replay) {
+    if let Some(path) = &args.replay {
+        match Replay::load(path) {
+            Ok(replay) => {
+                if replay.config_hash != config_hash() {
+                    warn!("Replay was recorded with different game settings, playback may differ");
+                }
+                info!("Playing back {} ({} ticks)", path.display(), replay.ticks());
+                return (RunMode::Playback, replay);
+            }
+            Err(e) => error!("Could not load replay {}", e),
+        }
+    }
+    let seed = seed.or(args.seed).unwrap_or_else(|| thread_rng().gen());
+    let mut record = args.record.as_deref().map(|path| numbered(path, run));
+    // Ghost runs are kept so that later runs with the same seed can race them
+    if args.ghost && record.is_none() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        record =
+            Some(PathBuf::from(REPLAY_DIR).join(format!("seed-{}-{}.replay", seed, now.as_secs())));
+    }
+    let ghost = args.ghost;
+    (RunMode::Live { record, ghost }, Replay::new(seed))
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let (mode, replay) = start_run(app.world.resource::<RunArgs>(), None, 1);
+        app.insert_resource(GameRng(StdRng::seed_from_u64(replay.seed)))
+            .insert_resource(ScenerySeed(!replay.seed))
+            .insert_resource(ReplayState {
+                run: 1,
+                mode,
+                replay,
+                tick: 0,
+                hawk_hits: 0,
+            })
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame).with_system(new_run.label(GameStep::NewRun)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(save_run))
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(finish_playback))
+            .add_system(
+                play_back_input
+                    .after(GameStep::Input)
+                    .before(GameStep::Movement)
+                    .with_run_criteria(SimulationRunning),
+            )
+            .add_system(
+                record_tick
+                    .after(GameStep::HawkSpawning)
+                    .with_run_criteria(SimulationRunning),
+            )
+            .add_system_to_stage(CoreStage::Last, save_on_exit);
+    }
+}
+
+// The first run was set up with the plugin, every later one gets a fresh start
+fn new_run(
+    args: Res<RunArgs>,
+    settings: Option<Res<Settings>>,
+    competition: Option<Res<Competition>>,
+    mut state: ResMut<ReplayState>,
+    mut rng: ResMut<GameRng>,
+    mut class: ResMut<DogClass>,
+) {
+    let seed = competition
+        .as_ref()
+        .and_then(|competition| competition.seed());
+    if state.tick > 0 || seed.map_or(false, |seed| seed != state.replay.seed) {
+        let run = state.run + 1;
+        let (mode, replay) = start_run(&args, seed, run);
+        *state = ReplayState {
+            run,
+            mode,
+            replay,
+            tick: 0,
+            hawk_hits: 0,
+        };
+    }
+    // Also rewinds a run that was left before its first tick
+    *rng = GameRng(StdRng::seed_from_u64(state.replay.seed));
+    // Played back with the dog it was recorded with, everyone in a competition
+    // runs the same class
+    *class = match state.mode {
+        RunMode::Playback => state.replay.dog,
+        RunMode::Live { .. } => competition
+            .and_then(|competition| competition.class())
+            .or_else(|| settings.map(|settings| settings.dog_class))
+            .unwrap_or_default(),
+    };
+    state.replay.dog = *class;
+}
+
+// Replaces the keyboard input with the recorded one during playback
+fn play_back_input(
+    state: Res<ReplayState>,
+    mut player_input: ResMut<PlayerInput>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !matches!(state.mode, RunMode::Playback) {
+        return;
+    }
+    match state.replay.input_at(state.tick) {
+        Some(input) => *player_input = input,
+        None => {
+            *player_input = PlayerInput::default();
+            // Only report once, the app exits at the end of this frame
+            if state.tick == state.replay.ticks() {
+                report_playback(&state, total_points.0, score.0);
+                exit.send(AppExit);
+            }
+        }
+    }
+}
+
+// A replay whose last tick costs the last life ends in game over, where the
+// simulation and play_back_input stop
+fn finish_playback(
+    state: Res<ReplayState>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if matches!(state.mode, RunMode::Playback) {
+        report_playback(&state, total_points.0, score.0);
+        exit.send(AppExit);
+    }
+}
+
+fn report_playback(state: &ReplayState, points: u32, score: u32) {
+    let replay = &state.replay;
+    if points == replay.points && score == replay.score && state.hawk_hits == replay.hawk_hits {
+        info!(
+            "Replay matched: {} bones, score {}, {} hawk hits in {} ticks",
+            points, score, state.hawk_hits, state.tick
+        );
+    } else {
+        warn!(
+            "Replay diverged: {} bones, score {}, {} hawk hits (recorded {} bones, score {}, {} hawk hits)",
+            points, score, state.hawk_hits, replay.points, replay.score, replay.hawk_hits
+        );
+    }
+}
+
+fn record_tick(
+    mut state: ResMut<ReplayState>,
+    player_input: Res<PlayerInput>,
+    total_points: Res<TotalPoints>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+    player_query: Query<&Transform, With<Player>>,
+    hawk_query: Query<&Transform, With<Hawk>>,
+    bone_query: Query<&Transform, With<Bone>>,
+) {
+    let state = &mut *state;
+    let tick = state.tick;
+    if matches!(state.mode, RunMode::Playback) && tick >= state.replay.ticks() {
+        return;
+    }
+    let hits = hawk_hits.iter().count() as u32;
+    state.hawk_hits += hits;
+    if let RunMode::Live { record, .. } = &state.mode {
+        let replay = &mut state.replay;
+        replay.push_input(*player_input);
+        if let Some(player_tf) = player_query.iter().next() {
+            replay.track.push(player_tf.translation.y.round() as i16);
+        }
+        replay.bone_ticks.extend(bone_pickups.iter().map(|_| tick));
+        replay.hit_ticks.extend((0..hits).map(|_| tick));
+        if record.is_some() {
+            replay.frames.push(Frame {
+                player: player_query
+                    .iter()
+                    .next()
+                    .map(pixel_position)
+                    .unwrap_or_default(),
+                hawks: hawk_query.iter().map(pixel_position).collect(),
+                bones: bone_query
+                    .iter()
+                    .map(|tf| {
+                        let (x, y) = pixel_position(tf);
+                        let direction = tf.rotation * Vec3::X;
+                        let angle = direction.y.atan2(direction.x);
+                        (x, y, angle.to_degrees().round() as i16)
+                    })
+                    .collect(),
+                points: total_points.0,
+            });
+        }
+    }
+    state.tick += 1;
+}
+
+fn pixel_position(transform: &Transform) -> (i16, i16) {
+    (
+        transform.translation.x.round() as i16,
+        transform.translation.y.round() as i16,
+    )
+}
+
+fn save_on_exit(
+    state: ResMut<ReplayState>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    ai: Option<Res<AiDog>>,
+    mut exit: EventReader<AppExit>,
+) {
+    if exit.iter().next().is_some() {
+        save_run(state, total_points, score, mode, ai);
+    }
+}
+
+fn save_run(
+    mut state: ResMut<ReplayState>,
+    total_points: Res<TotalPoints>,
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    ai: Option<Res<AiDog>>,
+) {
+    // Course runs don't play back, there is nothing to keep. An AI dog's run is
+    // no ghost for the player to race.
+    if *mode != GameMode::Balloon || ai.is_some() {
+        return;
+    }
+    let state = &mut *state;
+    // Taking the path saves every run only once, even if the app exits right after
+    if let RunMode::Live { record, .. } = &mut state.mode {
+        let path = match record.take() {
+            Some(path) => path,
+            None => return,
+        };
+        state.replay.points = total_points.0;
+        state.replay.score = score.0;
+        state.replay.hawk_hits = state.hawk_hits;
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        match state.replay.save(&path) {
+            Ok(()) => info!("Saved replay of {} ticks to {}", state.tick, path.display()),
+            Err(e) => error!("Could not save replay {}", e),
+        }
+    }
+}
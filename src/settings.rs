@@ -0,0 +1,100 @@
+//! Player settings, kept in `save/settings.ron` between sessions and changed in the
+//! settings menu. That includes the size class of the dog, see `dog`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data;
+use crate::dog::DogClass;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+// Start: --- Resources
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    // Bevy's audio plays everything at full volume, so these are only on and off
+    pub music: bool,
+    pub sound_effects: bool,
+    // Accessibility, every bit of hit feedback can be turned off on its own
+    pub screen_shake: bool,
+    pub hit_stop: bool,
+    pub damage_flash: bool,
+    // Of the dog in the next run
+    pub dog_class: DogClass,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            music: true,
+            sound_effects: true,
+            screen_shake: true,
+            hit_stop: true,
+            damage_flash: true,
+            dog_class: DogClass::Medium,
+        }
+    }
+}
+// End: --- Resources
+
+// Entries of the settings menu
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    Music,
+    SoundEffects,
+    ScreenShake,
+    HitStop,
+    DamageFlash,
+    DogClass,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 6] = [
+        Setting::DogClass,
+        Setting::Music,
+        Setting::SoundEffects,
+        Setting::ScreenShake,
+        Setting::HitStop,
+        Setting::DamageFlash,
+    ];
+
+    pub fn label(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        match self {
+            Setting::Music => format!("Music: {}", on_off(settings.music)),
+            Setting::SoundEffects => format!("Sound effects: {}", on_off(settings.sound_effects)),
+            Setting::ScreenShake => format!("Screen shake: {}", on_off(settings.screen_shake)),
+            Setting::HitStop => format!("Hit-stop: {}", on_off(settings.hit_stop)),
+            Setting::DamageFlash => format!("Damage flash: {}", on_off(settings.damage_flash)),
+            Setting::DogClass => format!("Dog size: {}", settings.dog_class.name()),
+        }
+    }
+
+    // Steps the dog size up or down, switches ignore the direction and toggle
+    pub fn change(self, settings: &mut Settings, up: bool) {
+        match self {
+            Setting::Music => settings.music = !settings.music,
+            Setting::SoundEffects => settings.sound_effects = !settings.sound_effects,
+            Setting::ScreenShake => settings.screen_shake = !settings.screen_shake,
+            Setting::HitStop => settings.hit_stop = !settings.hit_stop,
+            Setting::DamageFlash => settings.damage_flash = !settings.damage_flash,
+            Setting::DogClass => settings.dog_class = settings.dog_class.cycle(up),
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(data::load_saved::<Settings>(SETTINGS_FILE))
+            .add_system(save_settings);
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        data::store(SETTINGS_FILE, &*settings);
+    }
+}
@@ -0,0 +1,141 @@
+//! Lifetime statistics over every run, kept in `save/stats.ron` and shown on the
+//! statistics screen of the main menu.
+//!
+//! Like achievements, replays that are played back don't count, and neither do
+//! course runs.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bones::BoneKind;
+use crate::competition::AiDog;
+use crate::dog::DogClass;
+use crate::replay::{ReplayState, RunMode, TICKS_PER_SECOND};
+use crate::{
+    data, AppState, BalloonRunning, BonePickupEvent, GameMode, GameStep, HawkDodgedEvent,
+    HawkHitEvent, Player, RunTicks,
+};
+
+const STATS_FILE: &str = "stats.ron";
+
+// Start: --- Resources
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LifetimeStats {
+    runs_played: u32,
+    total_bones: u32,
+    hawks_dodged: u32,
+    hawks_hit: u32,
+    longest_run_ticks: u32,
+    ticks_aloft: u64,
+    ticks_on_ground: u64,
+}
+
+impl LifetimeStats {
+    // One line per statistic for the statistics screen
+    pub fn lines(&self) -> Vec<String> {
+        let minutes = |ticks: u64| ticks as f64 / TICKS_PER_SECOND / 60.0;
+        let total_minutes = minutes(self.ticks_aloft + self.ticks_on_ground);
+        let bones_per_minute = if total_minutes > 0.0 {
+            self.total_bones as f64 / total_minutes
+        } else {
+            0.0
+        };
+        vec![
+            format!("Runs played: {}", self.runs_played),
+            format!("Bones collected: {}", self.total_bones),
+            format!(
+                "Hawks dodged: {}  hit: {}",
+                self.hawks_dodged, self.hawks_hit
+            ),
+            format!(
+                "Longest run: {}",
+                format_duration(self.longest_run_ticks as u64)
+            ),
+            format!(
+                "Time aloft: {}  on the ground: {}",
+                format_duration(self.ticks_aloft),
+                format_duration(self.ticks_on_ground)
+            ),
+            format!("Bones per minute: {:.1}", bones_per_minute),
+        ]
+    }
+}
+// End: --- Resources
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(data::load_saved::<LifetimeStats>(STATS_FILE))
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(finish_run))
+            .add_system(
+                track_stats
+                    .with_run_criteria(BalloonRunning)
+                    .after(GameStep::HawkCollision),
+            );
+    }
+}
+
+fn format_duration(ticks: u64) -> String {
+    let seconds = (ticks as f64 / TICKS_PER_SECOND) as u64;
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+fn track_stats(
+    replay: Option<Res<ReplayState>>,
+    ai: Option<Res<AiDog>>,
+    mut stats: ResMut<LifetimeStats>,
+    mut bone_pickups: EventReader<BonePickupEvent>,
+    mut hawk_hits: EventReader<HawkHitEvent>,
+    mut hawk_dodges: EventReader<HawkDodgedEvent>,
+    class: Res<DogClass>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if replay.map_or(false, |replay| matches!(replay.mode, RunMode::Playback)) || ai.is_some() {
+        return;
+    }
+    let bones = bone_pickups
+        .iter()
+        .filter(|pickup| pickup.kind != BoneKind::Decoy)
+        .count() as u32;
+    let hits = hawk_hits.iter().count() as u32;
+    let dodges = hawk_dodges.iter().count() as u32;
+    let stats = &mut *stats;
+    stats.total_bones += bones;
+    stats.hawks_hit += hits;
+    stats.hawks_dodged += dodges;
+    for player_tf in player_query.iter() {
+        if player_tf.translation.y <= class.ground_y() {
+            stats.ticks_on_ground += 1;
+        } else {
+            stats.ticks_aloft += 1;
+        }
+    }
+}
+
+// Counts the run and writes everything it added
+fn finish_run(
+    replay: Option<Res<ReplayState>>,
+    ai: Option<Res<AiDog>>,
+    mode: Res<GameMode>,
+    run_ticks: Res<RunTicks>,
+    mut stats: ResMut<LifetimeStats>,
+) {
+    let playback = replay.map_or(false, |replay| matches!(replay.mode, RunMode::Playback));
+    if playback || ai.is_some() || *mode != GameMode::Balloon {
+        return;
+    }
+    stats.runs_played += 1;
+    stats.longest_run_ticks = stats.longest_run_ticks.max(run_ticks.0);
+    data::store(STATS_FILE, &*stats);
+}
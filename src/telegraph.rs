@@ -0,0 +1,109 @@
+//! Shows when a hawk is about to attack: it flashes, screeches and gets an
+//! exclamation mark above its head for as long as it telegraphs.
+
+use bevy::prelude::*;
+
+use crate::audio::PlaySound;
+use crate::hawks::{HawkAi, Phase};
+use crate::{GameStep, RunEntity};
+
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+const MARKER_COLOR: Color = Color::rgb(1.0, 0.25, 0.15);
+const FLASH_COLOR: Color = Color::rgb(1.0, 0.5, 0.4);
+// On/off phases of the flash per second
+const FLASH_RATE: f64 = 10.0;
+// Above the hawk's head
+const MARKER_HEIGHT: f32 = 45.0;
+
+// Start: --- Components
+// Follows its hawk while it telegraphs. Kept off the hawk itself, adding components
+// to hawks only in the game would change the order the simulation sees them in.
+#[derive(Component)]
+struct TelegraphMarker {
+    hawk: Entity,
+}
+// End: --- Components
+
+pub struct TelegraphPlugin;
+
+impl Plugin for TelegraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(show_telegraphs.after(GameStep::HawkMovement));
+    }
+}
+
+fn show_telegraphs(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut sounds: EventWriter<PlaySound>,
+    mut hawks: Query<
+        (
+            Entity,
+            &HawkAi,
+            &Transform,
+            Option<&mut Sprite>,
+            Option<&mut TextureAtlasSprite>,
+        ),
+        Without<TelegraphMarker>,
+    >,
+    mut markers: Query<(Entity, &TelegraphMarker, &mut Transform)>,
+) {
+    // Markers of hawks that attack by now or are gone
+    for (marker, telegraph, mut transform) in markers.iter_mut() {
+        match hawks.get(telegraph.hawk) {
+            Ok((_, ai, hawk_tf, _, _)) if ai.phase == Phase::Telegraph => {
+                transform.translation = hawk_tf.translation + Vec3::new(0.0, MARKER_HEIGHT, 1.0);
+            }
+            _ => commands.entity(marker).despawn(),
+        }
+    }
+
+    let flash_on = (time.seconds_since_startup() * FLASH_RATE) as u64 % 2 == 0;
+    for (hawk, ai, hawk_tf, sprite, atlas_sprite) in hawks.iter_mut() {
+        let active = ai.phase == Phase::Telegraph;
+        if active
+            && !markers
+                .iter()
+                .any(|(_, telegraph, _)| telegraph.hawk == hawk)
+        {
+            commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        "!",
+                        TextStyle {
+                            font: asset_server.load(FONT),
+                            font_size: 40.0,
+                            color: MARKER_COLOR,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_translation(
+                        hawk_tf.translation + Vec3::new(0.0, MARKER_HEIGHT, 1.0),
+                    ),
+                    ..default()
+                })
+                .insert(TelegraphMarker { hawk })
+                .insert(RunEntity);
+            sounds.send(PlaySound("hawk_screech"));
+        }
+        let color = if active && flash_on {
+            FLASH_COLOR
+        } else {
+            Color::WHITE
+        };
+        if let Some(mut sprite) = sprite {
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+        if let Some(mut sprite) = atlas_sprite {
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Replay viewer: steps through the recorded frames of a run.
+//!
+//! Space pauses, Left/Right step one tick, Up/Down change the speed between 0.25x
+//! and 4x, PageUp/PageDown skip five seconds, N jumps to just before the next hawk
+//! hit and T followed by a time (`75`, `1:15` or `75.5`) and Enter jumps there.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::replay::{Frame, Replay, TICKS_PER_SECOND};
+use crate::{BONE_HEIGHT, BONE_WIDTH};
+
+const SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+// How far before a hawk hit the viewer lands when jumping to it
+const HIT_LEAD_IN_SECONDS: f64 = 2.0;
+
+// Start: --- Components
+#[derive(Component)]
+struct ViewerDog;
+
+#[derive(Component)]
+struct ViewerHawk;
+
+#[derive(Component)]
+struct ViewerBone;
+
+#[derive(Component)]
+struct ViewerHud;
+// End: --- Components
+
+// Start: --- Resources
+struct ViewerState {
+    replay: Replay,
+    // Fractional tick so that slow speeds still advance
+    cursor: f64,
+    playing: bool,
+    speed: usize,
+    // Timestamp being typed after pressing T
+    time_entry: Option<String>,
+    // Tick of the hawk hit N jumped to last, so the next N goes on to the one after
+    last_hit: Option<u32>,
+}
+
+impl ViewerState {
+    fn last_tick(&self) -> f64 {
+        self.replay.frames.len().saturating_sub(1) as f64
+    }
+
+    fn tick(&self) -> usize {
+        self.cursor as usize
+    }
+
+    fn frame(&self) -> Option<&Frame> {
+        self.replay.frames.get(self.tick())
+    }
+
+    fn move_cursor(&mut self, tick: f64) {
+        self.cursor = tick.clamp(0.0, self.last_tick());
+    }
+
+    // Anywhere but to the next hit, N looks on from the new place
+    fn seek(&mut self, tick: f64) {
+        self.move_cursor(tick);
+        self.last_hit = None;
+    }
+}
+// End: --- Resources
+
+pub struct ReplayViewerPlugin(pub PathBuf);
+
+impl Plugin for ReplayViewerPlugin {
+    fn build(&self, app: &mut App) {
+        let replay = match Replay::load(&self.0) {
+            Ok(replay) => replay,
+            Err(e) => {
+                error!("Could not load replay {}", e);
+                Replay::default()
+            }
+        };
+        if replay.frames.is_empty() {
+            warn!("{} has no recorded frames to show", self.0.display());
+        }
+        app.insert_resource(ViewerState {
+            replay,
+            cursor: 0.0,
+            playing: true,
+            speed: 2,
+            time_entry: None,
+            last_hit: None,
+        })
+        .add_startup_system(setup_viewer)
+        .add_system(viewer_controls)
+        .add_system(time_entry.after(viewer_controls))
+        .add_system(advance_cursor.after(time_entry))
+        .add_system(show_dog.after(advance_cursor))
+        .add_system(show_hawks.after(advance_cursor))
+        .add_system(show_bones.after(advance_cursor))
+        .add_system(update_viewer_hud.after(advance_cursor))
+        .add_system(bevy::input::system::exit_on_esc_system);
+    }
+}
+
+fn setup_viewer(mut commands: Commands, asset_server: Res<AssetServer>, viewer: Res<ViewerState>) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands.spawn_bundle(UiCameraBundle::default());
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(viewer.replay.dog.balloon_size()),
+                ..default()
+            },
+            texture: asset_server.load("sprites/dog-with-balloons-white.png"),
+            transform: Transform::from_xyz(-400., 0., 10.),
+            ..default()
+        })
+        .insert(ViewerDog);
+
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            custom_size: Some(Vec2::new(1280.0, 70.0)),
+            ..default()
+        },
+        texture: asset_server.load("sprites/grass.png"),
+        transform: Transform::from_xyz(-0., -365., 20.),
+        ..default()
+    });
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.0),
+                    left: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: String::new(),
+                        style: style.clone(),
+                    },
+                    TextSection {
+                        value: "\nSpace pause  Left/Right step  Up/Down speed  PgUp/PgDn 5s  N next hit  T jump to time".to_string(),
+                        style: TextStyle {
+                            font_size: 16.0,
+                            ..style
+                        },
+                    },
+                ],
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ViewerHud);
+}
+
+fn viewer_controls(keyboard_input: Res<Input<KeyCode>>, mut viewer: ResMut<ViewerState>) {
+    // Keys are typed into the timestamp while it is being entered
+    if viewer.time_entry.is_some() {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        viewer.playing = !viewer.playing;
+    }
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        viewer.playing = false;
+        let tick = viewer.tick() as f64 + 1.0;
+        viewer.seek(tick);
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        viewer.playing = false;
+        let tick = viewer.tick() as f64 - 1.0;
+        viewer.seek(tick);
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        viewer.speed = (viewer.speed + 1).min(SPEEDS.len() - 1);
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        viewer.speed = viewer.speed.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::PageUp) {
+        let tick = viewer.cursor + 5.0 * TICKS_PER_SECOND;
+        viewer.seek(tick);
+    }
+    if keyboard_input.just_pressed(KeyCode::PageDown) {
+        let tick = viewer.cursor - 5.0 * TICKS_PER_SECOND;
+        viewer.seek(tick);
+    }
+    if keyboard_input.just_pressed(KeyCode::N) {
+        // The lead-in puts the cursor before the hit N went to, and maybe before
+        // others, so carry on after the later of the two
+        let after = (viewer.tick() as u32).max(viewer.last_hit.unwrap_or(0));
+        let next_hit = viewer
+            .replay
+            .hit_ticks
+            .iter()
+            .copied()
+            .find(|tick| *tick > after);
+        if let Some(tick) = next_hit {
+            // Hits in the first seconds of a run land on its start
+            viewer.move_cursor(tick as f64 - HIT_LEAD_IN_SECONDS * TICKS_PER_SECOND);
+            viewer.last_hit = Some(tick);
+            viewer.playing = false;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::T) {
+        viewer.time_entry = Some(String::new());
+    }
+}
+
+// Parses seconds given as `75`, `75.5` or `1:15`
+fn parse_timestamp(entry: &str) -> Option<f64> {
+    match entry.split_once(':') {
+        Some((minutes, seconds)) => {
+            Some(minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?)
+        }
+        None => entry.parse::<f64>().ok(),
+    }
+}
+
+fn time_entry(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut viewer: ResMut<ViewerState>,
+) {
+    let entry = match viewer.time_entry.as_mut() {
+        Some(entry) => entry,
+        None => {
+            characters.iter().for_each(drop);
+            return;
+        }
+    };
+    for event in characters.iter() {
+        if event.char.is_ascii_digit() || event.char == '.' || event.char == ':' {
+            entry.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        entry.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        if let Some(seconds) = parse_timestamp(entry) {
+            viewer.seek(seconds * TICKS_PER_SECOND);
+        }
+        viewer.time_entry = None;
+    }
+}
+
+fn advance_cursor(time: Res<Time>, mut viewer: ResMut<ViewerState>) {
+    if !viewer.playing {
+        return;
+    }
+    let tick = viewer.cursor + SPEEDS[viewer.speed] * TICKS_PER_SECOND * time.delta_seconds_f64();
+    viewer.move_cursor(tick);
+    if viewer.cursor >= viewer.last_tick() {
+        viewer.playing = false;
+    }
+}
+
+fn show_dog(viewer: Res<ViewerState>, mut query: Query<&mut Transform, With<ViewerDog>>) {
+    let frame = match viewer.frame() {
+        Some(frame) => frame,
+        None => return,
+    };
+    for mut transform in query.iter_mut() {
+        transform.translation.x = frame.player.0 as f32;
+        transform.translation.y = frame.player.1 as f32;
+    }
+}
+
+fn show_hawks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    viewer: Res<ViewerState>,
+    mut query: Query<(&mut Transform, &mut Visibility), With<ViewerHawk>>,
+) {
+    let hawks = viewer
+        .frame()
+        .map(|frame| frame.hawks.as_slice())
+        .unwrap_or_default();
+    let mut shown = 0;
+    for (mut transform, mut visibility) in query.iter_mut() {
+        visibility.is_visible = shown < hawks.len();
+        if let Some((x, y)) = hawks.get(shown) {
+            transform.translation = Vec3::new(*x as f32, *y as f32, 1.);
+        }
+        shown += 1;
+    }
+    // Grow the pool of hawk sprites, they are positioned on the next frame
+    for _ in shown..hawks.len() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(60.0, 60.0)),
+                    ..default()
+                },
+                texture: asset_server.load("sprites/hawk.png"),
+                transform: Transform::from_xyz(800., 0., 1.),
+                ..default()
+            })
+            .insert(ViewerHawk);
+    }
+}
+
+fn show_bones(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    viewer: Res<ViewerState>,
+    mut query: Query<(&mut Transform, &mut Visibility), With<ViewerBone>>,
+) {
+    let bones = viewer
+        .frame()
+        .map(|frame| frame.bones.as_slice())
+        .unwrap_or_default();
+    let mut shown = 0;
+    for (mut transform, mut visibility) in query.iter_mut() {
+        visibility.is_visible = shown < bones.len();
+        if let Some((x, y, angle)) = bones.get(shown) {
+            transform.translation = Vec3::new(*x as f32, *y as f32, 0.);
+            transform.rotation = Quat::from_rotation_z((*angle as f32).to_radians());
+        }
+        shown += 1;
+    }
+    for _ in shown..bones.len() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(BONE_WIDTH, BONE_HEIGHT)),
+                    ..default()
+                },
+                texture: asset_server.load("sprites/bone-yellow.png"),
+                transform: Transform::from_xyz(800., 0., 0.),
+                ..default()
+            })
+            .insert(ViewerBone);
+    }
+}
+
+fn update_viewer_hud(viewer: Res<ViewerState>, mut query: Query<&mut Text, With<ViewerHud>>) {
+    let tick = viewer.tick();
+    let seconds = tick as f64 / TICKS_PER_SECOND;
+    let points = viewer.frame().map(|frame| frame.points).unwrap_or_default();
+    let status = match &viewer.time_entry {
+        Some(entry) => format!("Jump to: {}_", entry),
+        None if viewer.playing => format!("Playing x{}", SPEEDS[viewer.speed]),
+        None => "Paused".to_string(),
+    };
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!(
+            "Tick {} / {}   {}:{:05.2}   Bones: {}   {}",
+            tick,
+            viewer.last_tick(),
+            (seconds / 60.0) as u32,
+            seconds % 60.0,
+            points,
+            status
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Warning arrows at the right edge of the screen for hawks that are about to fly
+//! in, at the height they will come in at. The sooner a hawk arrives the bigger and
+//! more solid its arrow.
+
+use bevy::prelude::*;
+
+use crate::{GameStep, Hawk, RunEntity, HAWK_LEAD_TICKS, HAWK_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+const ARROW_TEXTURE: &str = "sprites/warning-arrow.png";
+const ARROW_SIZE: f32 = 40.0;
+// Hawks further out than this many ticks get no arrow yet, they come in from
+// just as far out
+const WARNING_TICKS: f32 = HAWK_LEAD_TICKS;
+const MIN_SCALE: f32 = 0.5;
+const MIN_ALPHA: f32 = 0.3;
+const ARROW_Z: f32 = 30.0;
+
+// Start: --- Components
+// Kept off the hawk itself like the telegraph marker, so the simulation sees the
+// same hawks in the game as in the replay verifier
+#[derive(Component)]
+struct HawkWarning {
+    hawk: Entity,
+}
+// End: --- Components
+
+pub struct WarningPlugin;
+
+impl Plugin for WarningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(show_warnings.after(GameStep::HawkSpawning));
+    }
+}
+
+// How far from arriving on screen a hawk is, from 0.0 (right now) to 1.0 (just
+// warned about), or None if it is on screen already or too far out
+fn arrival(transform: &Transform, speed: f32) -> Option<f32> {
+    let distance = transform.translation.x - (WINDOW_WIDTH / 2.0 + HAWK_SIZE / 2.0);
+    if distance <= 0.0 {
+        return None;
+    }
+    // In whole ticks, a hawk that just came in is exactly WARNING_TICKS out give
+    // or take a rounding error
+    let ticks = (distance / speed.max(0.1)).round();
+    (ticks <= WARNING_TICKS).then(|| ticks / WARNING_TICKS)
+}
+
+fn arrow_position(transform: &Transform) -> Vec3 {
+    let edge = WINDOW_HEIGHT / 2.0 - ARROW_SIZE / 2.0;
+    Vec3::new(
+        WINDOW_WIDTH / 2.0 - ARROW_SIZE / 2.0,
+        transform.translation.y.clamp(-edge, edge),
+        ARROW_Z,
+    )
+}
+
+// Scale and transparency of an arrow
+fn arrow_look(progress: f32) -> (f32, f32) {
+    let closeness = 1.0 - progress;
+    (
+        MIN_SCALE + (1.0 - MIN_SCALE) * closeness,
+        MIN_ALPHA + (1.0 - MIN_ALPHA) * closeness,
+    )
+}
+
+fn show_warnings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hawks: Query<(Entity, &Hawk, &Transform), Without<HawkWarning>>,
+    mut warnings: Query<(Entity, &HawkWarning, &mut Transform, &mut Sprite)>,
+) {
+    for (warning, hawk_warning, mut transform, mut sprite) in warnings.iter_mut() {
+        let progress = hawks
+            .get(hawk_warning.hawk)
+            .ok()
+            .and_then(|(_, speed, hawk_tf)| arrival(hawk_tf, speed.0).map(|p| (p, hawk_tf)));
+        let (progress, hawk_tf) = match progress {
+            Some(found) => found,
+            None => {
+                commands.entity(warning).despawn();
+                continue;
+            }
+        };
+        let (scale, alpha) = arrow_look(progress);
+        transform.translation = arrow_position(hawk_tf);
+        transform.scale = Vec3::splat(scale);
+        sprite.color.set_a(alpha);
+    }
+
+    for (hawk, speed, hawk_tf) in hawks.iter() {
+        if warnings
+            .iter()
+            .any(|(_, warning, _, _)| warning.hawk == hawk)
+        {
+            continue;
+        }
+        let (scale, alpha) = match arrival(hawk_tf, speed.0) {
+            Some(progress) => arrow_look(progress),
+            None => continue,
+        };
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(ARROW_SIZE)),
+                    color: Color::rgba(1.0, 1.0, 1.0, alpha),
+                    ..default()
+                },
+                texture: asset_server.load(ARROW_TEXTURE),
+                transform: Transform {
+                    translation: arrow_position(hawk_tf),
+                    scale: Vec3::splat(scale),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(HawkWarning { hawk })
+            .insert(RunEntity);
+    }
+}
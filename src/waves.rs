@@ -0,0 +1,227 @@
+//! Hawk waves, defined in `assets/data/waves.ron`. A wave is a formation of hawks
+//! that comes in together: a V, a wall with a gap to fly through, a staggered line
+//! or any set of positions. Waves follow a script of fixed ticks at the start of a
+//! run, after it a random wave comes every so often.
+//!
+//! Waves are part of the simulation, so the file is included in the replay config
+//! hash like `hawks.ron`.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use crate::data;
+use crate::hawks::Behaviour;
+
+pub const WAVES_FILE: &str = "waves.ron";
+// Heights a formation has to stay within
+const LOWEST_Y: f32 = -300.0;
+const HIGHEST_Y: f32 = 350.0;
+
+// Start: --- Data
+#[derive(Deserialize, Debug, Clone)]
+pub enum Pattern {
+    // Leader in front, the others trailing behind it above and below
+    V {
+        count: u32,
+        spacing_x: f32,
+        spacing_y: f32,
+    },
+    // A column of hawks with `gap` of them missing at a random place
+    Wall {
+        count: u32,
+        spacing: f32,
+        gap: u32,
+    },
+    // A line that alternates between two heights `offset` apart
+    Staggered {
+        count: u32,
+        spacing: f32,
+        offset: f32,
+    },
+    // Hawks at the given offsets from the start of the wave, x grows to the right
+    Custom {
+        hawks: Vec<(f32, f32)>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WaveDef {
+    pub name: String,
+    pub pattern: Pattern,
+    // Pixels per tick, the same for every hawk so the formation keeps its shape
+    pub speed: f32,
+    #[serde(default)]
+    pub behaviour: Behaviour,
+    // Relative to the other weights for random waves, 0 for scripted only
+    #[serde(default)]
+    pub weight: u32,
+    // Ticks into the run before the wave can come at random
+    #[serde(default)]
+    pub from_tick: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScriptEntry {
+    pub tick: u32,
+    pub wave: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WaveConfig {
+    // Waves at fixed ticks of the run, in order
+    pub script: Vec<ScriptEntry>,
+    // Ticks between random waves once the script is done
+    pub interval: (u32, u32),
+    pub waves: Vec<WaveDef>,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        WaveConfig {
+            script: vec![],
+            interval: (600, 1200),
+            waves: vec![],
+        }
+    }
+}
+
+impl WaveConfig {
+    // Falls back to no waves at all when the interval or the order of the script is off
+    pub fn load() -> Self {
+        let config: WaveConfig = data::load(WAVES_FILE);
+        if config.interval.0 == 0 {
+            warn!(
+                "Random waves in {} need at least a tick between them",
+                WAVES_FILE
+            );
+            return WaveConfig::default();
+        }
+        if let Some(pair) = config
+            .script
+            .windows(2)
+            .find(|pair| pair[1].tick < pair[0].tick)
+        {
+            warn!(
+                "Wave {} at tick {} comes after wave {} at tick {} in {}",
+                pair[1].wave, pair[1].tick, pair[0].wave, pair[0].tick, WAVES_FILE
+            );
+            return WaveConfig::default();
+        }
+        for entry in config.script.iter() {
+            if config.find(&entry.wave).is_none() {
+                warn!("No wave named {} in {}", entry.wave, WAVES_FILE);
+            }
+        }
+        config
+    }
+
+    fn find(&self, name: &str) -> Option<&WaveDef> {
+        self.waves.iter().find(|wave| wave.name == name)
+    }
+}
+// End: --- Data
+
+// Start: --- Resources
+// Where the current run is in the script and when the next random wave comes
+#[derive(Default)]
+pub struct WaveDirector {
+    tick: u32,
+    next_script: usize,
+    next_random: Option<u32>,
+}
+
+impl WaveDirector {
+    // Advances by a tick and returns the wave due in it, if any
+    pub fn tick<'a>(&mut self, config: &'a WaveConfig, rng: &mut StdRng) -> Option<&'a WaveDef> {
+        self.tick += 1;
+        if let Some(entry) = config.script.get(self.next_script) {
+            if entry.tick > self.tick {
+                return None;
+            }
+            self.next_script += 1;
+            return config.find(&entry.wave);
+        }
+        let tick = self.tick;
+        let (min, max) = config.interval;
+        let due = *self
+            .next_random
+            .get_or_insert_with(|| tick + rng.gen_range(min..=max.max(min)));
+        if due > tick {
+            return None;
+        }
+        self.next_random = None;
+        let eligible: Vec<&WaveDef> = config
+            .waves
+            .iter()
+            .filter(|wave| wave.weight > 0 && wave.from_tick <= tick)
+            .collect();
+        eligible
+            .choose_weighted(rng, |wave| wave.weight)
+            .ok()
+            .copied()
+    }
+}
+// End: --- Resources
+
+// Offsets of the hawks of a wave from its start point
+pub fn formation(pattern: &Pattern, rng: &mut StdRng) -> Vec<Vec2> {
+    let mut offsets: Vec<Vec2> = match pattern {
+        Pattern::V {
+            count,
+            spacing_x,
+            spacing_y,
+        } => (0..*count)
+            .map(|index| {
+                // 0, then 1 and 1 below, 2 and 2 below...
+                let rank = ((index + 1) / 2) as f32;
+                let side = if index % 2 == 1 { 1.0 } else { -1.0 };
+                Vec2::new(rank * spacing_x, side * rank * spacing_y)
+            })
+            .collect(),
+        Pattern::Wall {
+            count,
+            spacing,
+            gap,
+        } => {
+            // The gap is never at the very top or bottom
+            let gap = (*gap).min(count.saturating_sub(2));
+            let start = rng.gen_range(1..=count.saturating_sub(gap + 1).max(1));
+            (0..*count)
+                .filter(|index| *index < start || *index >= start + gap)
+                .map(|index| Vec2::new(0.0, index as f32 * spacing))
+                .collect()
+        }
+        Pattern::Staggered {
+            count,
+            spacing,
+            offset,
+        } => (0..*count)
+            .map(|index| Vec2::new(index as f32 * spacing, (index % 2) as f32 * offset))
+            .collect(),
+        Pattern::Custom { hawks } => hawks.iter().map(|(x, y)| Vec2::new(*x, *y)).collect(),
+    };
+    if offsets.is_empty() {
+        return offsets;
+    }
+    // A random height that keeps the whole formation on screen
+    let low = offsets
+        .iter()
+        .map(|offset| offset.y)
+        .fold(f32::MAX, f32::min);
+    let high = offsets
+        .iter()
+        .map(|offset| offset.y)
+        .fold(f32::MIN, f32::max);
+    let (min_y, max_y) = (LOWEST_Y - low, HIGHEST_Y - high);
+    let y = if min_y < max_y {
+        rng.gen_range(min_y..max_y)
+    } else {
+        (min_y + max_y) / 2.0
+    };
+    for offset in offsets.iter_mut() {
+        offset.y += y;
+    }
+    offsets
+}